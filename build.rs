@@ -0,0 +1,5 @@
+fn main() {
+    // links the Node.js addon against the symbols provided by the node binary
+    #[cfg(feature = "node")]
+    napi_build::setup();
+}
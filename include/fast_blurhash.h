@@ -0,0 +1,87 @@
+#ifndef FAST_BLURHASH_H
+#define FAST_BLURHASH_H
+
+/* This file is generated by cbindgen from src/ffi.rs, do not edit it manually. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+// Maximum length of a blurhash including the terminating NUL character
+#define FAST_BLURHASH_MAX_LEN ((((1 + 1) + 4) + (2 * ((9 * 9) - 1))) + 1)
+
+// Status codes returned by the C functions, mirroring [`BlurhashError`].
+typedef enum FastBlurhashStatus {
+  // The function succeeded
+  FAST_BLURHASH_STATUS_OK = 0,
+  // See [`BlurhashError::InvalidLength`]
+  FAST_BLURHASH_STATUS_INVALID_LENGTH = 1,
+  // See [`BlurhashError::InvalidPunch`]
+  FAST_BLURHASH_STATUS_INVALID_PUNCH = 2,
+  // See [`BlurhashError::BadFormat`] and [`Base83ConversionError::InvalidChar`]
+  FAST_BLURHASH_STATUS_INVALID_CHAR = 3,
+  // See [`BlurhashError::BadFormat`] and [`Base83ConversionError::Overflow`]
+  FAST_BLURHASH_STATUS_OVERFLOW = 4,
+  // See [`BlurhashError::UnsupportedMode`]
+  FAST_BLURHASH_STATUS_UNSUPPORTED_MODE = 5,
+  // A pointer is null, a dimension is zero, the stride is too small, the
+  // number of channels is not 3 or 4 or the number of components is not
+  // between 1 and 9.
+  FAST_BLURHASH_STATUS_INVALID_ARGUMENT = 6,
+  // The output buffer is too small
+  FAST_BLURHASH_STATUS_BUFFER_TOO_SMALL = 7,
+} FastBlurhashStatus;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Returns a static NUL-terminated description of a status code. The status is
+// taken as an `int` so values that are not part of [`FastBlurhashStatus`] are
+// described as an unknown status.
+const char *fast_blurhash_strerror(int status);
+
+// Computes the blurhash of an 8-bit sRGB image. `channels` is 3 for RGB
+// pixels and 4 for RGBA pixels (alpha is ignored), `stride` is the number of
+// bytes between the start of two rows. The NUL-terminated blurhash is written
+// to `out` which must be able to hold `out_len` bytes
+// ([`FAST_BLURHASH_MAX_LEN`] is always enough).
+//
+// # Safety
+//
+// `pixels` must point to at least `stride * (height - 1) + width * channels`
+// readable bytes and `out` to `out_len` writable bytes.
+enum FastBlurhashStatus fast_blurhash_encode(const uint8_t *pixels,
+                                             size_t width,
+                                             size_t height,
+                                             size_t stride,
+                                             size_t channels,
+                                             size_t x_components,
+                                             size_t y_components,
+                                             char *out,
+                                             size_t out_len);
+
+// Decodes a NUL-terminated blurhash into an 8-bit sRGB image of (width * height)
+// pixels provided by the caller. `channels` is 3 for RGB pixels and 4 for RGBA
+// pixels (alpha will always be 255), `stride` is the number of bytes between the
+// start of two rows.
+//
+// # Safety
+//
+// `blurhash` must be a valid NUL-terminated string and `out` must point to
+// at least `stride * (height - 1) + width * channels` writable bytes.
+enum FastBlurhashStatus fast_blurhash_decode(const char *blurhash,
+                                             float punch,
+                                             uint8_t *out,
+                                             size_t width,
+                                             size_t height,
+                                             size_t stride,
+                                             size_t channels);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* FAST_BLURHASH_H */
@@ -0,0 +1,359 @@
+//! Non-standard alpha channel extension of the BlurHash format
+//!
+//! **This extension is not part of the wolt/blurhash specification.** The
+//! generated blurhashes are rejected by [`decode`] and by other BlurHash
+//! implementations, only use it when both ends of the pipeline rely on this
+//! crate.
+//!
+//! The alpha plane is transformed using the same number of X and Y components
+//! as the color planes and is appended after a standard blurhash:
+//!
+//! | Digits | Content |
+//! |---|---|
+//! | 1 | Quantised maximum value of the alpha ACs |
+//! | 2 | Average alpha (DC) between 0 and 255 |
+//! | 1 per AC | Alpha ACs quantised between 0 and 82 |
+//!
+//! The alpha of the pixels is retrieved using [`AsLinear::alpha`], colors
+//! without an alpha channel are considered opaque. Note that `u32` pixels are
+//! expected to be `0xAARRGGBB`: an alpha of zero means fully transparent.
+//!
+//! [`decode`]: ../fn.decode.html
+//!
+//! #### Example
+//! ```
+//! use fast_blurhash::alpha;
+//!
+//! let image: [[u8; 4]; 4] = [
+//!     [255, 0, 0, 255], [255, 0, 0,   0],
+//!     [0, 0, 255,   0], [0, 0, 255, 255],
+//! ];
+//! let blurhash = alpha::compute_dct(&image, 2, 2, 3, 3).into_blurhash();
+//! let image: Vec<[u8; 4]> = alpha::decode(&blurhash, 1.).unwrap().to_rgba8(32, 32);
+//! ```
+
+use std::f32::consts::PI;
+use crate::{DCTResult, BlurhashError, multiply_basis, inv_multiply_basis, normalize_and_max};
+use crate::base83::{self, encode_fixed_to};
+use crate::convert::*;
+
+/// AlphaDCTResult is the result of a Discrete Cosine Transform performed on
+/// both the color planes and the alpha plane of an image.
+#[derive(Default, Clone, Debug)]
+pub struct AlphaDCTResult {
+    /// The DCT of the color planes
+    color: DCTResult,
+    /// The absolute maximum value of the alpha alternative currents
+    alpha_max: f32,
+    /// The alpha currents stored in the same order as the color currents
+    alpha: Vec<f32>,
+}
+
+impl AlphaDCTResult {
+    /// Store the result of a DCT with an alpha plane
+    pub fn new(color: DCTResult, alpha_max: f32, alpha: Vec<f32>) -> AlphaDCTResult {
+        assert!(alpha.len() == color.currents.len());
+
+        AlphaDCTResult { color, alpha_max, alpha }
+    }
+
+    /// Convert the computed color and alpha frequencies into a base83 string
+    /// using the non-standard alpha extension.
+    pub fn into_blurhash(self) -> String {
+        encode(&self)
+    }
+
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image. This function allocates a vector of (width * height) pixels, the
+    /// converter receives the color in linear space and the alpha between 0 and 1.
+    pub fn to_image<T>(&self, width: usize, height: usize, convert: fn(Linear, f32) -> T) -> Vec<T> {
+        let (x_comps, y_comps) = self.color.dim();
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            let percent_y = y as f32 / height as f32;
+            for x in 0..width {
+                let percent_x = x as f32 / width as f32;
+
+                let mut col = inv_multiply_basis(x_comps, y_comps,
+                    percent_x, percent_y, &self.color.currents);
+
+                col[0] = col[0].clamp(0., 1.);
+                col[1] = col[1].clamp(0., 1.);
+                col[2] = col[2].clamp(0., 1.);
+
+                let alpha = inv_multiply_basis_alpha(x_comps, y_comps,
+                    percent_x, percent_y, &self.alpha);
+
+                pixels.push(convert(col, alpha.clamp(0., 1.)));
+            }
+        }
+
+        pixels
+    }
+
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image. This function allocates a vector of (width * height) pixels in
+    /// the sRGB space as in [RR, GG, BB, AA] where alpha is not premultiplied.
+    pub fn to_rgba8(&self, width: usize, height: usize) -> Vec<[u8; 4]> {
+        self.to_image(width, height, |col, alpha| [
+            linear_to_srgb(col[0]),
+            linear_to_srgb(col[1]),
+            linear_to_srgb(col[2]),
+            (alpha * 255. + 0.5) as u8
+        ])
+    }
+
+    /// Retrieve the DCT of the color planes
+    pub fn color(&self) -> &DCTResult {
+        &self.color
+    }
+
+    /// Retrieve the currents of the alpha plane, stored in the same order as
+    /// the currents of the color planes.
+    pub fn alpha_currents(&self) -> &[f32] {
+        &self.alpha
+    }
+
+    /// Drop the alpha plane and keep the DCT of the color planes which can
+    /// be encoded into a standard blurhash.
+    pub fn into_color(self) -> DCTResult {
+        self.color
+    }
+}
+
+/// Compute the blurhash string from the DCT result using the non-standard alpha
+/// extension. This function allocates a string of length
+/// (1 + 1 + 4 + 2 * (components - 1)) + (1 + 2 + (components - 1)).
+pub fn encode(dct: &AlphaDCTResult) -> String {
+    let AlphaDCTResult { color, alpha_max, alpha } = dct;
+    let mut blurhash = crate::encode(color);
+    blurhash.reserve(1 + 2 + alpha.len() - 1);
+
+    let quantised_max = (alpha_max * 166. - 0.5).floor().clamp(0., 82.);
+    encode_fixed_to(quantised_max as u32, 1, &mut blurhash);
+    let alpha_max = (quantised_max + 1.) / 166.;
+
+    encode_fixed_to((alpha[0].clamp(0., 1.) * 255. + 0.5) as u32, 2, &mut blurhash);
+
+    for &ac in alpha.iter().skip(1) {
+        encode_fixed_to(encode_alpha_ac(ac, alpha_max), 1, &mut blurhash);
+    }
+
+    blurhash
+}
+
+/// Decode a blurhash using the non-standard alpha extension to retrieve the DCT
+/// results of the color and alpha planes. The punch only applies to the colors.
+pub fn decode(blurhash: &str, punch: f32) -> Result<AlphaDCTResult, BlurhashError> {
+    let blurhash = blurhash.as_bytes();
+    let (x_components, y_components) = crate::decode_header(blurhash)?;
+
+    let current_count = x_components * y_components;
+    let color_len = 1 + 1 + 4 + 2 * (current_count - 1);
+    if blurhash.len() != color_len + 1 + 2 + (current_count - 1) {
+        return Err(BlurhashError::InvalidLength)
+    }
+
+    let color = crate::decode_bytes(&blurhash[..color_len], punch)?;
+
+    let alpha_max = base83::decode_bytes(&blurhash[color_len..(color_len + 1)])? + 1;
+    let alpha_max = alpha_max as f32 / 166.;
+
+    let mut alpha = Vec::with_capacity(current_count);
+    alpha.push(base83::decode_bytes(&blurhash[(color_len + 1)..(color_len + 3)])? as f32 / 255.);
+
+    for idx in (color_len + 3)..blurhash.len() {
+        let ac = base83::decode_bytes(&blurhash[idx..(idx + 1)])?;
+        alpha.push(decode_alpha_ac(ac, alpha_max));
+    }
+
+    Ok(AlphaDCTResult { color, alpha_max, alpha })
+}
+
+/// Compute the Discrete Cosine Transform on the color and alpha planes of an
+/// image. The slice must be long enough (it must have at least width * height
+/// items).
+///
+/// Note: To generate a valid blurhash, the number of X or/and Y components
+/// must be between 1 and 9. This is a limitation of the encoding scheme.
+pub fn compute_dct<T: AsLinear>(image: &[T], width: usize, height: usize, x_components: usize, y_components: usize) -> AlphaDCTResult {
+    assert!(image.len() >= width * height);
+    let mut currents: Vec<Factor> = vec![[0., 0., 0.]; x_components * y_components];
+    let mut alpha: Vec<f32> = vec![0.; x_components * y_components];
+
+    for y in 0..height {
+        let percent_y = y as f32 / height as f32;
+        for x in 0..width {
+            let percent_x = x as f32 / width as f32;
+
+            let pixel = &image[y * width + x];
+            multiply_basis(x_components, y_components, percent_x, percent_y, &pixel.as_linear(), &mut currents);
+            multiply_basis_alpha(x_components, y_components, percent_x, percent_y, pixel.alpha(), &mut alpha);
+        }
+    }
+
+    let ac_max = normalize_and_max(&mut currents, width * height);
+    let alpha_max = normalize_alpha_and_max(&mut alpha, width * height);
+
+    AlphaDCTResult {
+        color: DCTResult { ac_max, currents, x_components, y_components },
+        alpha_max,
+        alpha
+    }
+}
+
+/// Compute the Discrete Cosine Transform on the color and alpha planes of an
+/// image. The iterator must be long enough (it must have at least width * height
+/// items).
+///
+/// Note: To generate a valid blurhash, the number of X or/and Y components
+/// must be between 1 and 9. This is a limitation of the encoding scheme.
+pub fn compute_dct_iter<T: AsLinear>(image: impl Iterator<Item = T>, width: usize, height: usize, x_components: usize, y_components: usize) -> AlphaDCTResult {
+    let mut currents: Vec<Factor> = vec![[0., 0., 0.]; x_components * y_components];
+    let mut alpha: Vec<f32> = vec![0.; x_components * y_components];
+
+    for (i, pixel) in image.take(width * height).enumerate() {
+        let percent_y = (i / width) as f32 / height as f32;
+        let percent_x = (i % width) as f32 / width as f32;
+
+        multiply_basis(x_components, y_components, percent_x, percent_y, &pixel.as_linear(), &mut currents);
+        multiply_basis_alpha(x_components, y_components, percent_x, percent_y, pixel.alpha(), &mut alpha);
+    }
+
+    let ac_max = normalize_and_max(&mut currents, width * height);
+    let alpha_max = normalize_alpha_and_max(&mut alpha, width * height);
+
+    AlphaDCTResult {
+        color: DCTResult { ac_max, currents, x_components, y_components },
+        alpha_max,
+        alpha
+    }
+}
+
+/// Same as [`multiply_basis`] for a single channel.
+///
+/// [`multiply_basis`]: ../fn.multiply_basis.html
+#[inline]
+fn multiply_basis_alpha(x_comps: usize, y_comps: usize, x: f32, y: f32, alpha: f32, currents: &mut [f32]) {
+    for comp_y in 0..y_comps {
+        let base_y = (PI * comp_y as f32 * y).cos();
+
+        for comp_x in 0..x_comps {
+            let base_x = (PI * comp_x as f32 * x).cos();
+            currents[comp_y * x_comps + comp_x] += base_y * base_x * alpha;
+        }
+    }
+}
+
+/// Same as [`inv_multiply_basis`] for a single channel.
+///
+/// [`inv_multiply_basis`]: ../fn.inv_multiply_basis.html
+#[inline]
+fn inv_multiply_basis_alpha(x_comps: usize, y_comps: usize, x: f32, y: f32, currents: &[f32]) -> f32 {
+    let mut alpha = 0.;
+    for comp_y in 0..y_comps {
+        let base_y = (PI * comp_y as f32 * y).cos();
+
+        for comp_x in 0..x_comps {
+            let base_x = (PI * comp_x as f32 * x).cos();
+            alpha += base_y * base_x * currents[comp_y * x_comps + comp_x];
+        }
+    }
+
+    alpha
+}
+
+/// Same as [`normalize_and_max`] for a single channel. Returns 0 if there
+/// are no ACs.
+///
+/// [`normalize_and_max`]: ../fn.normalize_and_max.html
+fn normalize_alpha_and_max(currents: &mut [f32], len: usize) -> f32 {
+    let len = len as f32;
+    currents[0] /= len;
+
+    let mut alpha_max = 0f32;
+    let norm = 2. / len;
+    for ac in currents.iter_mut().skip(1) {
+        *ac *= norm;
+        alpha_max = alpha_max.max(ac.abs());
+    }
+
+    alpha_max
+}
+
+/// Encodes an alpha AC to an u32 to be encoded into a 1-digit base83
+fn encode_alpha_ac(ac: f32, alpha_max: f32) -> u32 {
+    (sign_pow(ac / alpha_max, 0.5) * 41. + 41.5).floor().clamp(0., 82.) as u32
+}
+
+/// Decodes an alpha AC from an u32
+fn decode_alpha_ac(n: u32, alpha_max: f32) -> f32 {
+    sign_pow((n as f32 - 41.) / 41., 2.) * alpha_max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_opaque() {
+        let image: Vec<Rgb> = vec![[255, 127, 55]; 32 * 32];
+        let dct = compute_dct(&image, 32, 32, 3, 3);
+        let standard = crate::compute_dct(&image, 32, 32, 3, 3).into_blurhash();
+
+        let blurhash = dct.into_blurhash();
+        assert!(blurhash.starts_with(&standard));
+        assert_eq!(blurhash.len(), standard.len() + 1 + 2 + 8);
+
+        // the sampling used by the reference algorithm leaks a bit of the
+        // average into the first ACs, hence the tolerance
+        let inv = decode(&blurhash, 1.).unwrap();
+        for (i, pixel) in inv.to_rgba8(32, 32).iter().enumerate() {
+            assert!(pixel[3] >= 225, "Expected opaque pixel got {pixel:?} at index {i}");
+        }
+    }
+
+    #[test]
+    fn test_encode_decode_transparent_half() {
+        let image: Vec<u32> = (0..32 * 32)
+            .map(|i| if i % 32 < 16 { 0xFFFFFFFF } else { 0x00000000 })
+            .collect();
+
+        let blurhash = compute_dct_iter(image.into_iter(), 32, 32, 4, 3).into_blurhash();
+        let inv = decode(&blurhash, 1.).unwrap();
+        assert!((inv.alpha_currents()[0] - 0.5).abs() < 0.01);
+
+        let generated = inv.to_rgba8(32, 32);
+        for y in 0..32 {
+            let (left, right) = (generated[y * 32 + 4], generated[y * 32 + 27]);
+            assert!(left[3] > 200, "Expected opaque left side got {left:?}");
+            assert!(right[3] < 55, "Expected transparent right side got {right:?}");
+        }
+    }
+
+    #[test]
+    fn test_standard_decode_rejects_alpha() {
+        let image: [u32; 4] = [0xFFFF0000, 0x00FF0000, 0x000000FF, 0xFF0000FF];
+        let blurhash = compute_dct(&image, 2, 2, 3, 3).into_blurhash();
+
+        assert_eq!(crate::decode(&blurhash, 1.).unwrap_err(), BlurhashError::InvalidLength);
+        assert_eq!(decode(&crate::compute_dct(&image, 2, 2, 3, 3).into_blurhash(), 1.).unwrap_err(),
+            BlurhashError::InvalidLength);
+    }
+
+    #[test]
+    fn test_decode_non_ascii() {
+        let image: [u32; 4] = [0xFFFF0000, 0x00FF0000, 0x000000FF, 0xFF0000FF];
+        let blurhash = compute_dct(&image, 2, 2, 1, 1).into_blurhash();
+        assert_eq!(blurhash.len(), 9);
+
+        for i in [0, 4, 7] {
+            let mut bytes = blurhash.clone().into_bytes();
+            bytes.splice(i..i + 2, "°".bytes());
+            let invalid = String::from_utf8(bytes).unwrap();
+            assert_eq!(decode(&invalid, 1.).unwrap_err(),
+                BlurhashError::BadFormat(crate::base83::Base83ConversionError::InvalidChar), "{invalid}");
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! base83 encode and decode utilities
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Base83ConversionError {
     InvalidChar,
     Overflow
@@ -99,6 +100,46 @@ pub fn encode_fixed_to(mut n: u32, iters: u8, str: &mut String) {
     }
 }
 
+/// Encodes an u32 to base83 into a byte buffer without allocating. This function
+/// writes up to 6 characters at the start of the buffer and returns the number
+/// of characters written. Panics if the buffer is too small.
+pub fn encode_into(mut n: u32, buf: &mut [u8]) -> usize {
+    if n == 0 {
+        buf[0] = CHARACTERS[0];
+        return 1;
+    }
+
+    let mut stack: [u8; 6] = [0; 6];
+    let mut i = 0;
+
+    while n > 0 {
+        stack[i] = CHARACTERS[(n % 83) as usize];
+        n /= 83;
+        i += 1;
+    }
+
+    let len = i;
+    for c in &mut buf[..len] { // write in the reverse order
+        i -= 1;
+        *c = stack[i];
+    }
+    len
+}
+
+/// Encodes an u32 to a fixed size base83 string into a byte buffer without
+/// allocating. This function writes `iters` characters at the start of the
+/// buffer and returns `iters`. Panics if the buffer is too small.
+pub fn encode_fixed_into(mut n: u32, iters: u8, buf: &mut [u8]) -> usize {
+    assert!(iters <= 6);
+    let iters = iters as usize;
+
+    for c in buf[..iters].iter_mut().rev() {
+        *c = CHARACTERS[(n % 83) as usize];
+        n /= 83;
+    }
+    iters
+}
+
 const DIGITS: [u8; 256] = [
     0, 0, 0, 0, 0, 0, 0, 0,
     0, 0, 0, 0, 0, 0, 0, 0, // 16
@@ -149,6 +190,11 @@ const DIGITS: [u8; 256] = [
 
 ];
 
+/// Returns true if the character is part of the base83 character set.
+pub fn is_base83(c: char) -> bool {
+    c.is_ascii() && CHARACTERS.contains(&(c as u8))
+}
+
 /// Decodes an base83-encoded ascii string to an u32. Note that this function
 /// does not perform any runtime check on the input string, any ascii character
 /// that is not part of the base83 character set.
@@ -165,21 +211,29 @@ pub fn decode_ascii(s: &str) -> u32 {
 /// overflow). Note that this function will ignore any ascii character that is not
 /// part of the base83 character set.
 pub fn decode(s: &str) -> Result<u32, Base83ConversionError> {
+    decode_bytes(s.as_bytes())
+}
+
+/// Decodes base83-encoded bytes to an u32, see [`decode`]. This function
+/// returns an error if the bytes do not contain a valid u32 (in case of
+/// **non-ascii** bytes or u32 overflow).
+pub fn decode_bytes(s: &[u8]) -> Result<u32, Base83ConversionError> {
     let mut n: u32 = 0;
 
-    let mut chars = s.chars();
+    let mut bytes = s.iter();
     for _ in 0..5 { // no overflow until 6th character
-        match chars.next() {
-            Some(c) if c.is_ascii() => {
+        match bytes.next() {
+            Some(&c) if c.is_ascii() => {
                 n = n * 83 + DIGITS[c as usize] as u32;
             },
+            // non-ascii characters are only encoded with non-ascii bytes in UTF-8
             Some(_) => return Err(Base83ConversionError::InvalidChar),
             None => return Ok(n) // end of string
         }
     }
 
-    match chars.next() {
-        Some(c) if c.is_ascii() => {
+    match bytes.next() {
+        Some(&c) if c.is_ascii() => {
             n.checked_mul(83u32) // overflow check
                 .ok_or(Base83ConversionError::Overflow)?
                 .checked_add(DIGITS[c as usize] as u32)
@@ -206,6 +260,11 @@ mod tests {
                 assert_eq!(decode($base83), Ok($val));
                 assert_eq!(decode_ascii($base83), $val);
                 assert_eq!(decode(&encode($val)), Ok($val));
+
+                let mut buf = [0u8; 6];
+                let len = encode_into($val, &mut buf);
+                assert_eq!(&buf[..len], $base83.as_bytes());
+                assert_eq!(decode_bytes(&buf[..len]), Ok($val));
             }
         };
 
@@ -225,6 +284,11 @@ mod tests {
                 assert_eq!(encode_fixed($val, $base83.len() as u8), $base83);
                 assert_eq!(decode(&s), Ok($val));
                 assert_eq!(decode_ascii(&s), $val);
+
+                let mut buf = [0u8; 6];
+                assert_eq!(encode_fixed_into($val, $base83.len() as u8, &mut buf), $base83.len());
+                assert_eq!(&buf[..$base83.len()], $base83.as_bytes());
+                assert_eq!(decode_bytes(&buf[..$base83.len()]), Ok($val));
             }
         };
 
@@ -256,14 +320,24 @@ mod tests {
         (test_enc_fixed_max, u32::MAX, "17fd^]")
     }
 
+    #[test]
+    fn check_charset() {
+        assert!(CHARACTERS.iter().all(|&c| is_base83(c as char)));
+        assert!(!is_base83(' '));
+        assert!(!is_base83('"'));
+        assert!(!is_base83('°'));
+    }
+
     #[test]
     fn decode_invalid() {
         assert_eq!(decode("BAD°"), Err(Base83ConversionError::InvalidChar));
+        assert_eq!(decode_bytes(b"BA\xFF"), Err(Base83ConversionError::InvalidChar));
     }
 
     #[test]
     fn decode_overflow() {
         assert_eq!(decode("18fd^]"), Err(Base83ConversionError::Overflow));
         assert_eq!(decode("17fd^^"), Err(Base83ConversionError::Overflow));
+        assert_eq!(decode_bytes(b"18fd^]"), Err(Base83ConversionError::Overflow));
     }
 }
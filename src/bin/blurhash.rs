@@ -0,0 +1,258 @@
+//! `blurhash` command-line tool to generate and inspect blurhashes
+//! (requires the `cli` feature)
+
+use std::collections::HashMap;
+use std::io::{self, BufRead};
+use std::process::ExitCode;
+use fast_blurhash::{decode, encode_image, convert::linear_to_srgb};
+
+const USAGE: &str = "\
+Usage: blurhash <command> [options]
+
+Commands:
+  encode [files...] [-x 4] [-y 3]
+      Compute the blurhash of images. Reads the list of files from stdin (one
+      per line) when no file or `-` is given and prints `<file>\\t<blurhash>`.
+  decode <blurhash> [-w 32] [-h 32] [-p 1] -o <out.png>
+      Generate a PNG placeholder from a blurhash.
+  inspect [blurhashes...]
+      Print the components of blurhashes. Reads the blurhashes from stdin
+      (one per line) when no blurhash or `-` is given.
+
+Options:
+  -x, -y    Number of X and Y components (between 1 and 9)
+  -w, -h    Width and height of the generated placeholder
+  -p        Punch, increases the contrast of the placeholder
+  -o        Output file
+  --help    Print this message";
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    match run(&args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(msg) => {
+            eprintln!("blurhash: {msg}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Run a command and returns false if some of the inputs could not be processed
+fn run(args: &[String]) -> Result<bool, String> {
+    let Some((command, args)) = args.split_first() else {
+        return Err(format!("missing command\n\n{USAGE}"))
+    };
+
+    match command.as_str() {
+        "encode" | "decode" | "inspect" if args.iter().any(|arg| arg == "--help") => {
+            println!("{USAGE}");
+            Ok(true)
+        },
+        "encode" => encode(args),
+        "decode" => decode_to_png(args),
+        "inspect" => inspect(args),
+        "--help" | "help" => {
+            println!("{USAGE}");
+            Ok(true)
+        },
+        _ => Err(format!("unknown command `{command}`\n\n{USAGE}"))
+    }
+}
+
+/// Parsed arguments of a command: the positional arguments and the value of
+/// each option.
+struct Args<'a> {
+    positional: Vec<&'a str>,
+    options: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Args<'a> {
+    /// Parse the arguments of a command accepting the given options (which all
+    /// take a value).
+    fn parse(args: &'a [String], allowed: &[&str]) -> Result<Args<'a>, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            if arg.starts_with('-') && arg != "-" {
+                if !allowed.contains(&arg.as_str()) {
+                    return Err(format!("unknown option `{arg}`"))
+                }
+                let value = args.next().ok_or_else(|| format!("missing value for `{arg}`"))?;
+                options.insert(arg.as_str(), value.as_str());
+            } else {
+                positional.push(arg.as_str());
+            }
+        }
+
+        Ok(Args { positional, options })
+    }
+
+    /// Retrieve the value of an option, or the default value if it is missing.
+    fn get<T: std::str::FromStr>(&self, option: &str, default: T) -> Result<T, String> {
+        match self.options.get(option) {
+            Some(value) => value.parse().map_err(|_| format!("invalid value `{value}` for `{option}`")),
+            None => Ok(default)
+        }
+    }
+
+    /// Retrieve the positional arguments, or the lines of stdin if there are
+    /// none or if the only argument is `-`.
+    fn inputs(&self) -> Result<Vec<String>, String> {
+        if !self.positional.is_empty() && self.positional != ["-"] {
+            return Ok(self.positional.iter().map(|s| s.to_string()).collect())
+        }
+
+        let mut inputs = Vec::new();
+        for line in io::stdin().lock().lines() {
+            let line = line.map_err(|err| format!("unable to read stdin: {err}"))?;
+            if !line.trim().is_empty() {
+                inputs.push(line.trim().to_string());
+            }
+        }
+        Ok(inputs)
+    }
+}
+
+fn encode(args: &[String]) -> Result<bool, String> {
+    let args = Args::parse(args, &["-x", "-y"])?;
+    let x_components: usize = args.get("-x", 4)?;
+    let y_components: usize = args.get("-y", 3)?;
+    if !(1..=9).contains(&x_components) || !(1..=9).contains(&y_components) {
+        return Err("the number of X and Y components must be between 1 and 9".to_string())
+    }
+
+    let batch = args.positional.len() != 1 || args.positional == ["-"];
+    let mut success = true;
+    for file in args.inputs()? {
+        let blurhash = match image::open(&file) {
+            Ok(img) => encode_image(&img, x_components, y_components),
+            Err(err) => {
+                eprintln!("blurhash: {file}: {err}");
+                success = false;
+                continue;
+            }
+        };
+
+        if batch {
+            println!("{file}\t{blurhash}");
+        } else {
+            println!("{blurhash}");
+        }
+    }
+
+    Ok(success)
+}
+
+fn decode_to_png(args: &[String]) -> Result<bool, String> {
+    let args = Args::parse(args, &["-w", "-h", "-p", "-o"])?;
+    let [blurhash] = args.positional[..] else {
+        return Err("decode expects exactly one blurhash".to_string())
+    };
+    let width: usize = args.get("-w", 32)?;
+    let height: usize = args.get("-h", 32)?;
+    let punch: f32 = args.get("-p", 1.)?;
+    let out: String = args.get("-o", String::new())?;
+    if width == 0 || height == 0 {
+        return Err("the width and the height must be greater than 0".to_string())
+    }
+    if out.is_empty() {
+        return Err("missing output file `-o`".to_string())
+    }
+
+    let dct = decode(blurhash, punch).map_err(|err| format!("{blurhash}: {err}"))?;
+    std::fs::write(&out, dct.to_png(width, height)).map_err(|err| format!("{out}: {err}"))?;
+    Ok(true)
+}
+
+fn inspect(args: &[String]) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let (mut success, mut first) = (true, true);
+    for blurhash in args.inputs()? {
+        let dct = match decode(&blurhash, 1.) {
+            Ok(dct) => dct,
+            Err(err) => {
+                eprintln!("blurhash: {blurhash}: {err}");
+                success = false;
+                continue;
+            }
+        };
+
+        if !first {
+            println!();
+        }
+        first = false;
+
+        let (x_components, y_components) = dct.dim();
+        let [r, g, b] = dct.dc().map(linear_to_srgb);
+        println!("blurhash: {blurhash}");
+        println!("components: {x_components}x{y_components}");
+        println!("average color: #{r:02x}{g:02x}{b:02x}");
+        println!("max AC: {:.4}", dct.ac_max());
+        for (j, ac) in dct.acs().iter().enumerate() {
+            let (x, y) = ((j + 1) % x_components, (j + 1) / x_components);
+            println!("AC ({x}, {y}): [{:+.4}, {:+.4}, {:+.4}]", ac[0], ac[1], ac[2]);
+        }
+    }
+
+    Ok(success)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let raw = args(&["a.png", "-x", "5", "b.png", "-y", "2"]);
+        let args = Args::parse(&raw, &["-x", "-y"]).unwrap();
+        assert_eq!(args.positional, ["a.png", "b.png"]);
+        assert_eq!(args.get("-x", 4), Ok(5));
+        assert_eq!(args.get("-y", 3), Ok(2));
+        assert_eq!(args.get("-w", 32), Ok(32));
+
+        let raw = self::args(&["-"]);
+        assert_eq!(Args::parse(&raw, &[]).unwrap().positional, ["-"]);
+    }
+
+    #[test]
+    fn test_parse_args_invalid() {
+        assert_eq!(Args::parse(&args(&["-z", "1"]), &["-x"]).err(), Some("unknown option `-z`".to_string()));
+        assert_eq!(Args::parse(&args(&["-x"]), &["-x"]).err(), Some("missing value for `-x`".to_string()));
+
+        let raw = args(&["-x", "four"]);
+        let args = Args::parse(&raw, &["-x"]).unwrap();
+        assert_eq!(args.get("-x", 4usize), Err("invalid value `four` for `-x`".to_string()));
+    }
+
+    #[test]
+    fn test_run_invalid() {
+        assert!(run(&[]).unwrap_err().starts_with("missing command"));
+        assert!(run(&args(&["resize"])).unwrap_err().starts_with("unknown command `resize`"));
+        assert!(run(&args(&["encode", "-x", "10", "a.png"])).is_err());
+        assert!(run(&args(&["decode", "LlMF%n00%#MwS|WCWEM{R*bbWBbH"])).is_err());
+        assert!(run(&args(&["decode", "-o", "out.png"])).is_err());
+        assert!(run(&args(&["decode", "00TSUA", "-w", "0", "-o", "out.png"])).is_err());
+        assert!(run(&args(&["decode", "00TSUA", "-h", "0", "-o", "out.png"])).is_err());
+        assert!(run(&args(&["resize", "--help"])).is_err());
+    }
+
+    #[test]
+    fn test_bad_hashes() {
+        // reported as a failure without stopping nor panicking
+        assert_eq!(run(&args(&["inspect", "°0TSUA", "LlMF%n00", "~0TSUA"])), Ok(false));
+        assert_eq!(run(&args(&["inspect", "00TSUA", "°0TSUA"])), Ok(false));
+        assert_eq!(run(&args(&["inspect", "00TSUA"])), Ok(true));
+
+        let err = run(&args(&["decode", "°0TSUA", "-o", "out.png"])).unwrap_err();
+        assert!(err.starts_with("°0TSUA: The blurhash contains invalid base83 codes"), "{err}");
+    }
+}
@@ -0,0 +1,147 @@
+//! Validated blurhash strings
+
+use std::fmt;
+use std::str::FromStr;
+use crate::{BlurhashError, DCTResult, decode};
+use crate::base83::{self, Base83ConversionError};
+
+/// A blurhash string which is guaranteed to be valid: it only contains base83
+/// characters and its length matches the number of components stored in its
+/// header. The header values are parsed on demand without decoding the whole
+/// blurhash.
+///
+/// #### Example
+/// ```
+/// use fast_blurhash::Blurhash;
+///
+/// let blurhash: Blurhash = "LlMF%n00%#MwS|WCWEM{R*bbWBbH".parse().unwrap();
+/// assert_eq!(blurhash.components(), (4, 3));
+/// let image: Vec<u32> = blurhash.decode(1.).unwrap().to_rgba(32, 32);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Blurhash(String);
+
+impl Blurhash {
+    /// Validate a blurhash string without decoding its components.
+    pub fn new(blurhash: impl Into<String>) -> Result<Blurhash, BlurhashError> {
+        let blurhash = blurhash.into();
+        validate(&blurhash)?;
+        Ok(Blurhash(blurhash))
+    }
+
+    /// Retrieve the blurhash as a string slice
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Retrieve the underlying blurhash string
+    pub fn into_string(self) -> String {
+        self.0
+    }
+
+    /// Retrieve the number of X and Y components (x_components, y_components)
+    /// from the header of the blurhash.
+    pub fn components(&self) -> (usize, usize) {
+        let total = base83::decode_ascii(&self.0[..1]) as usize;
+        ((total % 9) + 1, (total / 9) + 1)
+    }
+
+    /// Retrieve the quantised absolute maximum value of the alternative currents
+    /// from the header of the blurhash.
+    pub fn max_ac(&self) -> f32 {
+        (base83::decode_ascii(&self.0[1..2]) + 1) as f32 / 166.
+    }
+
+    /// Retrieve the average color of the image (the DC) in the sRGB space
+    /// as in [RR, GG, BB].
+    pub fn average_color(&self) -> [u8; 3] {
+        let [_, r, g, b] = base83::decode_ascii(&self.0[2..6]).to_be_bytes();
+        [r, g, b]
+    }
+
+    /// Decode the blurhash to retrieve the DCT results, see [`decode`].
+    ///
+    /// [`decode`]: fn.decode.html
+    pub fn decode(&self, punch: f32) -> Result<DCTResult, BlurhashError> {
+        decode(&self.0, punch)
+    }
+}
+
+impl FromStr for Blurhash {
+    type Err = BlurhashError;
+
+    fn from_str(blurhash: &str) -> Result<Self, Self::Err> {
+        Blurhash::new(blurhash)
+    }
+}
+
+impl fmt::Display for Blurhash {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(&self.0)
+    }
+}
+
+impl AsRef<str> for Blurhash {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<Blurhash> for String {
+    fn from(blurhash: Blurhash) -> Self {
+        blurhash.0
+    }
+}
+
+/// Check that the blurhash only contains base83 characters and that its length
+/// matches the number of components stored in the header.
+fn validate(blurhash: &str) -> Result<(), BlurhashError> {
+    if !blurhash.chars().all(base83::is_base83) {
+        return Err(BlurhashError::BadFormat(Base83ConversionError::InvalidChar))
+    }
+
+    let (x_components, y_components) = crate::decode_header(blurhash.as_bytes())?;
+    if blurhash.len() != 1 + 1 + 4 + 2 * (x_components * y_components - 1) {
+        return Err(BlurhashError::InvalidLength)
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_blurhash() {
+        let blurhash = Blurhash::new("LlMF%n00%#MwS|WCWEM{R*bbWBbH").unwrap();
+        assert_eq!(blurhash.as_str(), "LlMF%n00%#MwS|WCWEM{R*bbWBbH");
+        assert_eq!(blurhash.decode(1.).unwrap().dim(), (4, 3));
+    }
+
+    #[test]
+    fn test_header_accessors() {
+        let blurhash: Blurhash = "LlMF%n00%#MwS|WCWEM{R*bbWBbH".parse().unwrap();
+        let dct = blurhash.decode(1.).unwrap();
+
+        assert_eq!(blurhash.components(), dct.dim());
+        assert_eq!(blurhash.max_ac(), (base83::decode("l").unwrap() + 1) as f32 / 166.);
+        let [_, r, g, b] = crate::convert::to_rgb(*dct.dc()).to_be_bytes();
+        assert_eq!(blurhash.average_color(), [r, g, b]);
+
+        let white: Blurhash = "00TSUA".parse().unwrap();
+        assert_eq!(white.components(), (1, 1));
+        assert_eq!(white.average_color(), [255, 255, 255]);
+        assert_eq!(white.to_string(), "00TSUA");
+        assert_eq!(white.as_ref(), "00TSUA");
+    }
+
+    #[test]
+    fn test_invalid_blurhash() {
+        assert_eq!(Blurhash::new(""), Err(BlurhashError::InvalidLength));
+        assert_eq!(Blurhash::new("LlMF%n00%#MwS|WCWEM{R*bbWBb"), Err(BlurhashError::InvalidLength));
+        assert_eq!(Blurhash::new("LlMF%n00%#MwS|WCWEM{R*bbWB H"),
+            Err(BlurhashError::BadFormat(Base83ConversionError::InvalidChar)));
+        assert_eq!(Blurhash::new("~0TSUA"), Err(BlurhashError::UnsupportedMode));
+    }
+}
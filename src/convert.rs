@@ -1,5 +1,7 @@
 //! Color conversion and BlurHash specific encoding utilities
 
+use crate::transfer::{Srgb, TransferFunction};
+
 /// RGB Color in the linear space
 pub type Linear = [f32; 3];
 /// RGB Frequencies of a specific cosine transform
@@ -11,6 +13,31 @@ pub type Rgb = [u8; 3];
 pub trait AsLinear {
     /// Returns the color represented in linear space.
     fn as_linear(&self) -> Linear;
+
+    /// Returns the opacity of the color between 0 (transparent) and 1 (opaque).
+    /// Colors without an alpha channel are always opaque, but types with an
+    /// alpha channel must return it: it is used by the [`AlphaMode`] of the
+    /// encoding options (except `Ignore`) and by the non-standard [`alpha`]
+    /// extension.
+    ///
+    /// [`AlphaMode`]: ../options/enum.AlphaMode.html
+    /// [`alpha`]: ../alpha/index.html
+    fn alpha(&self) -> f32 {
+        1.
+    }
+
+    /// Whether the colors are always gray, in which case the three channels
+    /// returned by [`as_linear`](Self::as_linear) are equal and [`compute_dct`]
+    /// only transforms a single channel using [`as_linear_luma`](Self::as_linear_luma).
+    ///
+    /// [`compute_dct`]: ../fn.compute_dct.html
+    const IS_LUMA: bool = false;
+
+    /// Returns the luminance of a gray color in linear space, only used when
+    /// [`IS_LUMA`](Self::IS_LUMA) is true.
+    fn as_linear_luma(&self) -> f32 {
+        self.as_linear()[0]
+    }
 }
 
 impl AsLinear for [u8; 3] {
@@ -29,12 +56,20 @@ impl AsLinear for [u8; 4] {
     fn as_linear(&self) -> Linear {
         [srgb_to_linear(self[0]), srgb_to_linear(self[1]), srgb_to_linear(self[2])]
     }
+
+    fn alpha(&self) -> f32 {
+        self[3] as f32 / 255.
+    }
 }
 
 impl AsLinear for &[u8; 4] {
     fn as_linear(&self) -> Linear {
         [srgb_to_linear(self[0]), srgb_to_linear(self[1]), srgb_to_linear(self[2])]
     }
+
+    fn alpha(&self) -> f32 {
+        self[3] as f32 / 255.
+    }
 }
 
 impl AsLinear for u32 {
@@ -43,26 +78,313 @@ impl AsLinear for u32 {
          srgb_to_linear(((self >>  8) & 0xFF) as u8), // green
          srgb_to_linear(((self >>  0) & 0xFF) as u8)] // blue
     }
+
+    fn alpha(&self) -> f32 {
+        ((self >> 24) & 0xFF) as f32 / 255.
+    }
+}
+
+impl AsLinear for u8 {
+    const IS_LUMA: bool = true;
+
+    fn as_linear(&self) -> Linear {
+        [srgb_to_linear(*self); 3]
+    }
+
+    fn as_linear_luma(&self) -> f32 {
+        srgb_to_linear(*self)
+    }
+}
+
+impl AsLinear for &u8 {
+    const IS_LUMA: bool = true;
+
+    fn as_linear(&self) -> Linear {
+        [srgb_to_linear(**self); 3]
+    }
+
+    fn as_linear_luma(&self) -> f32 {
+        srgb_to_linear(**self)
+    }
+}
+
+impl AsLinear for [u8; 2] {
+    const IS_LUMA: bool = true;
+
+    fn as_linear(&self) -> Linear {
+        [srgb_to_linear(self[0]); 3]
+    }
+
+    fn as_linear_luma(&self) -> f32 {
+        srgb_to_linear(self[0])
+    }
+
+    fn alpha(&self) -> f32 {
+        self[1] as f32 / 255.
+    }
+}
+
+impl AsLinear for &[u8; 2] {
+    const IS_LUMA: bool = true;
+
+    fn as_linear(&self) -> Linear {
+        [srgb_to_linear(self[0]); 3]
+    }
+
+    fn as_linear_luma(&self) -> f32 {
+        srgb_to_linear(self[0])
+    }
+
+    fn alpha(&self) -> f32 {
+        self[1] as f32 / 255.
+    }
+}
+
+impl AsLinear for u16 {
+    const IS_LUMA: bool = true;
+
+    fn as_linear(&self) -> Linear {
+        [srgb16_to_linear(*self); 3]
+    }
+
+    fn as_linear_luma(&self) -> f32 {
+        srgb16_to_linear(*self)
+    }
+}
+
+impl AsLinear for &u16 {
+    const IS_LUMA: bool = true;
+
+    fn as_linear(&self) -> Linear {
+        [srgb16_to_linear(**self); 3]
+    }
+
+    fn as_linear_luma(&self) -> f32 {
+        srgb16_to_linear(**self)
+    }
+}
+
+impl AsLinear for [u16; 3] {
+    fn as_linear(&self) -> Linear {
+        [srgb16_to_linear(self[0]), srgb16_to_linear(self[1]), srgb16_to_linear(self[2])]
+    }
+}
+
+impl AsLinear for &[u16; 3] {
+    fn as_linear(&self) -> Linear {
+        [srgb16_to_linear(self[0]), srgb16_to_linear(self[1]), srgb16_to_linear(self[2])]
+    }
+}
+
+impl AsLinear for [u16; 4] {
+    fn as_linear(&self) -> Linear {
+        [srgb16_to_linear(self[0]), srgb16_to_linear(self[1]), srgb16_to_linear(self[2])]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3] as f32 / 65535.
+    }
+}
+
+impl AsLinear for &[u16; 4] {
+    fn as_linear(&self) -> Linear {
+        [srgb16_to_linear(self[0]), srgb16_to_linear(self[1]), srgb16_to_linear(self[2])]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3] as f32 / 65535.
+    }
+}
+
+/// The channels are already in linear space and may exceed 1 (see [`ToneMap`])
+///
+/// [`ToneMap`]: ../options/enum.ToneMap.html
+impl AsLinear for [f32; 3] {
+    fn as_linear(&self) -> Linear {
+        *self
+    }
+}
+
+impl AsLinear for &[f32; 3] {
+    fn as_linear(&self) -> Linear {
+        **self
+    }
+}
+
+/// The color channels are already in linear space and may exceed 1 (see
+/// [`ToneMap`]), the alpha channel is between 0 and 1.
+///
+/// [`ToneMap`]: ../options/enum.ToneMap.html
+impl AsLinear for [f32; 4] {
+    fn as_linear(&self) -> Linear {
+        [self[0], self[1], self[2]]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3].clamp(0., 1.)
+    }
+}
+
+impl AsLinear for &[f32; 4] {
+    fn as_linear(&self) -> Linear {
+        [self[0], self[1], self[2]]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3].clamp(0., 1.)
+    }
+}
+
+/// Half-precision floating-point colors in linear space (requires the `half`
+/// feature)
+#[cfg(feature = "half")]
+impl AsLinear for [half::f16; 3] {
+    fn as_linear(&self) -> Linear {
+        self.map(half::f16::to_f32)
+    }
+}
+
+#[cfg(feature = "half")]
+impl AsLinear for &[half::f16; 3] {
+    fn as_linear(&self) -> Linear {
+        self.map(half::f16::to_f32)
+    }
+}
+
+#[cfg(feature = "half")]
+impl AsLinear for [half::f16; 4] {
+    fn as_linear(&self) -> Linear {
+        [self[0].to_f32(), self[1].to_f32(), self[2].to_f32()]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3].to_f32().clamp(0., 1.)
+    }
+}
+
+#[cfg(feature = "half")]
+impl AsLinear for &[half::f16; 4] {
+    fn as_linear(&self) -> Linear {
+        [self[0].to_f32(), self[1].to_f32(), self[2].to_f32()]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3].to_f32().clamp(0., 1.)
+    }
+}
+
+/// Converts a color in linear space to any kind of Color, the inverse of
+/// [`AsLinear`] used by [`DCTResult::render`]. The channels of the color are
+/// between 0 and 1.
+///
+/// [`DCTResult::render`]: ../struct.DCTResult.html#method.render
+pub trait FromLinear {
+    /// Returns the color represented by a color in linear space. Colors with
+    /// an alpha channel are opaque.
+    fn from_linear(col: Linear) -> Self;
+}
+
+impl FromLinear for [u8; 3] {
+    fn from_linear(col: Linear) -> Self {
+        [linear_to_srgb(col[0]), linear_to_srgb(col[1]), linear_to_srgb(col[2])]
+    }
+}
+
+impl FromLinear for [u8; 4] {
+    fn from_linear(col: Linear) -> Self {
+        [linear_to_srgb(col[0]), linear_to_srgb(col[1]), linear_to_srgb(col[2]), 255]
+    }
+}
+
+/// 0xAARRGGBB in hex, same layout as `AsLinear`
+impl FromLinear for u32 {
+    fn from_linear(col: Linear) -> Self {
+        0xFF000000 | to_rgb(col)
+    }
+}
+
+/// The luminance of the color with the BT.709 coefficients
+impl FromLinear for u8 {
+    fn from_linear(col: Linear) -> Self {
+        linear_to_srgb(linear_luma(col))
+    }
+}
+
+impl FromLinear for [u8; 2] {
+    fn from_linear(col: Linear) -> Self {
+        [linear_to_srgb(linear_luma(col)), 255]
+    }
+}
+
+impl FromLinear for u16 {
+    fn from_linear(col: Linear) -> Self {
+        linear_to_srgb16(linear_luma(col))
+    }
+}
+
+impl FromLinear for [u16; 3] {
+    fn from_linear(col: Linear) -> Self {
+        [linear_to_srgb16(col[0]), linear_to_srgb16(col[1]), linear_to_srgb16(col[2])]
+    }
+}
+
+impl FromLinear for [u16; 4] {
+    fn from_linear(col: Linear) -> Self {
+        [linear_to_srgb16(col[0]), linear_to_srgb16(col[1]), linear_to_srgb16(col[2]), 65535]
+    }
+}
+
+impl FromLinear for [f32; 3] {
+    fn from_linear(col: Linear) -> Self {
+        col
+    }
+}
+
+impl FromLinear for [f32; 4] {
+    fn from_linear(col: Linear) -> Self {
+        [col[0], col[1], col[2], 1.]
+    }
+}
+
+/// Half-precision floating-point colors in linear space (requires the `half`
+/// feature)
+#[cfg(feature = "half")]
+impl FromLinear for [half::f16; 3] {
+    fn from_linear(col: Linear) -> Self {
+        col.map(half::f16::from_f32)
+    }
+}
+
+#[cfg(feature = "half")]
+impl FromLinear for [half::f16; 4] {
+    fn from_linear(col: Linear) -> Self {
+        [half::f16::from_f32(col[0]), half::f16::from_f32(col[1]), half::f16::from_f32(col[2]), half::f16::ONE]
+    }
+}
+
+/// Luminance of a color in linear space with the BT.709 coefficients
+fn linear_luma(col: Linear) -> f32 {
+    0.2126 * col[0] + 0.7152 * col[1] + 0.0722 * col[2]
 }
 
 /// Convert a single channel in linear space to sRGB space
 pub fn linear_to_srgb(linear: f32) -> u8 {
-    let linear = linear.max(0.).min(1.);
-    if linear <= 0.0031308 {
-        (linear * 12.92 * 255. + 0.5).floor() as u8
-    } else {
-        ((1.055 * linear.powf(1. / 2.4) - 0.055) * 255. + 0.5).floor() as u8
-    }
+    (Srgb.encode(linear.clamp(0., 1.)) * 255. + 0.5).floor() as u8
+}
+
+/// Convert a single channel in linear space to 16-bit sRGB space
+pub fn linear_to_srgb16(linear: f32) -> u16 {
+    (Srgb.encode(linear.clamp(0., 1.)) * 65535. + 0.5).floor() as u16
 }
 
 /// Convert a single channel in sRGB space to linear space
 pub fn srgb_to_linear(pixel: u8) -> f32 {
-    let normalized = pixel as f32 / 255.;
-    if normalized <= 0.04045 {
-        normalized / 12.92
-    } else {
-        ((normalized + 0.055) / 1.055).powf(2.4)
-    }
+    Srgb.decode(pixel as f32 / 255.)
+}
+
+/// Convert a single 16-bit channel in sRGB space to linear space
+pub fn srgb16_to_linear(pixel: u16) -> f32 {
+    Srgb.decode(pixel as f32 / 65535.)
 }
 
 /// Encodes a linear color to an u32 represented as RRGGBB in hex. This function
@@ -0,0 +1,127 @@
+//! CSS gradient output to display placeholders without decoding them in the browser
+
+use std::fmt::Write;
+use crate::DCTResult;
+use crate::convert::*;
+
+impl DCTResult {
+    /// Approximate the image of this DCT Result with CSS gradients. The image is
+    /// split into horizontal bands (two per Y component), each band being a
+    /// `linear-gradient` with two color stops per X component. The DC is used
+    /// as the background color.
+    ///
+    /// The returned string contains the CSS declarations to be inlined in the
+    /// style attribute of an element, for example:
+    /// `background-color:#8b6b4f;background-image:linear-gradient(...),...;...`
+    pub fn to_css_gradient(&self) -> String {
+        let (rows, cols) = self.css_gradient_dim();
+        let stops = self.sample_cells(cols, rows);
+
+        let mut css = String::with_capacity(64 + rows * (48 + cols * 16));
+        let _ = write!(css, "background-color:{};background-image:", hex(to_rgb(*self.dc())));
+
+        for (row, colors) in stops.chunks(cols).enumerate() {
+            if row > 0 {
+                css.push(',');
+            }
+
+            css.push_str("linear-gradient(90deg");
+            for (col, &color) in colors.iter().enumerate() {
+                let _ = write!(css, ",{} {}%", hex(color), percent((col as f32 + 0.5) / cols as f32));
+            }
+            css.push(')');
+        }
+
+        css.push_str(";background-position:");
+        for row in 0..rows {
+            if row > 0 {
+                css.push(',');
+            }
+
+            // a percentage aligns the same point of the band and of the element
+            let position = if rows > 1 { row as f32 / (rows - 1) as f32 } else { 0. };
+            let _ = write!(css, "0 {}%", percent(position));
+        }
+
+        let _ = write!(css, ";background-size:100% {}%;background-repeat:no-repeat", percent(1. / rows as f32));
+        css
+    }
+
+    /// Retrieve the number of bands (rows) and color stops per band (cols)
+    /// used by [`DCTResult::to_css_gradient`].
+    fn css_gradient_dim(&self) -> (usize, usize) {
+        (self.y_components * 2, self.x_components * 2)
+    }
+}
+
+/// Format an RRGGBB color as a CSS hex color
+pub(crate) fn hex(color: u32) -> String {
+    format!("#{color:06x}")
+}
+
+/// Format a ratio between 0 and 1 as a percentage with at most 3 decimals
+fn percent(ratio: f32) -> String {
+    let s = format!("{:.3}", ratio * 100.);
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode;
+
+    /// Rasterize the CSS gradient the same way a browser would: pick the band
+    /// of the pixel and interpolate linearly between the color stops in sRGB.
+    fn rasterize(dct: &DCTResult, width: usize, height: usize) -> Vec<[u8; 3]> {
+        let (rows, cols) = dct.css_gradient_dim();
+        let stops = dct.sample_cells(cols, rows);
+        let channel = |color: u32, i: usize| ((color >> (16 - 8 * i)) & 0xFF) as f32;
+
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row = (y * rows) / height;
+            for x in 0..width {
+                let pos = ((x as f32 + 0.5) / width as f32 * cols as f32 - 0.5)
+                    .clamp(0., (cols - 1) as f32);
+                let (left, right) = (pos.floor() as usize, pos.ceil() as usize);
+                let t = pos.fract();
+
+                let (a, b) = (stops[row * cols + left], stops[row * cols + right]);
+                let mut pixel = [0; 3];
+                for (i, p) in pixel.iter_mut().enumerate() {
+                    *p = (channel(a, i) * (1. - t) + channel(b, i) * t + 0.5) as u8;
+                }
+                pixels.push(pixel);
+            }
+        }
+
+        pixels
+    }
+
+    #[test]
+    fn test_css_gradient_format() {
+        let dct = decode("00TSUA", 1.).unwrap();
+        assert_eq!(dct.to_css_gradient(), "background-color:#ffffff;\
+            background-image:linear-gradient(90deg,#ffffff 25%,#ffffff 75%),\
+            linear-gradient(90deg,#ffffff 25%,#ffffff 75%);\
+            background-position:0 0%,0 100%;background-size:100% 50%;background-repeat:no-repeat");
+    }
+
+    #[test]
+    fn test_css_gradient_error() {
+        let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let expected = dct.to_rgb8(32, 32);
+        let actual = rasterize(&dct, 32, 32);
+
+        let error = expected.iter().flatten().zip(actual.iter().flatten())
+            .map(|(&a, &b)| (a as f32 - b as f32).abs())
+            .sum::<f32>() / (32. * 32. * 3.);
+        assert!(error < 8., "Mean absolute error too big: {error}");
+
+        let (rows, cols) = dct.css_gradient_dim();
+        let css = dct.to_css_gradient();
+        for stop in dct.sample_cells(cols, rows) {
+            assert!(css.contains(&hex(stop)));
+        }
+    }
+}
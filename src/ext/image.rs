@@ -0,0 +1,99 @@
+//! Integration with the `image` crate (requires the `image` feature)
+
+use ::image::{DynamicImage, Luma, LumaA, Rgb, Rgba, RgbaImage};
+use crate::{DCTResult, compute_dct_iter};
+use crate::convert::*;
+
+impl_as_linear! {
+    Rgb<u8> => |p| [srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2])], 1.;
+    Rgba<u8> => |p| [srgb_to_linear(p[0]), srgb_to_linear(p[1]), srgb_to_linear(p[2])], p[3] as f32 / 255.;
+    Luma<u8> => |p| [srgb_to_linear(p[0]); 3], 1., luma: srgb_to_linear(p[0]);
+    LumaA<u8> => |p| [srgb_to_linear(p[0]); 3], p[1] as f32 / 255., luma: srgb_to_linear(p[0]);
+    Rgb<u16> => |p| [srgb16_to_linear(p[0]), srgb16_to_linear(p[1]), srgb16_to_linear(p[2])], 1.;
+    Rgba<u16> => |p| [srgb16_to_linear(p[0]), srgb16_to_linear(p[1]), srgb16_to_linear(p[2])], p[3] as f32 / 65535.;
+    Luma<u16> => |p| [srgb16_to_linear(p[0]); 3], 1., luma: srgb16_to_linear(p[0]);
+    LumaA<u16> => |p| [srgb16_to_linear(p[0]); 3], p[1] as f32 / 65535., luma: srgb16_to_linear(p[0]);
+}
+
+/// Compute the blurhash of an image loaded with the `image` crate. 8-bit and
+/// 16-bit images are read without any conversion, floating-point images are
+/// converted to 16-bit first.
+///
+/// Note: To generate a valid blurhash, the number of X or/and Y components
+/// must be between 1 and 9. This is a limitation of the encoding scheme.
+pub fn encode_image(image: &DynamicImage, x_components: usize, y_components: usize) -> String {
+    let (width, height) = (image.width() as usize, image.height() as usize);
+
+    match image {
+        DynamicImage::ImageLuma8(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageLumaA8(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageRgb8(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageRgba8(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageLuma16(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageLumaA16(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageRgb16(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        DynamicImage::ImageRgba16(img) => compute_dct_iter(img.pixels(), width, height, x_components, y_components),
+        _ => compute_dct_iter(image.to_rgba16().pixels(), width, height, x_components, y_components),
+    }.into_blurhash()
+}
+
+impl DCTResult {
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image as an `image::RgbaImage` of (width * height) pixels (alpha will
+    /// always be 255). Requires the `image` feature.
+    pub fn to_rgba_image(&self, width: u32, height: u32) -> RgbaImage {
+        let pixels = self.to_rgba8(width as usize, height as usize);
+        RgbaImage::from_vec(width, height, pixels.into_iter().flatten().collect())
+            .expect("the buffer has exactly width * height pixels")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ::image::{GrayImage, ImageBuffer, RgbImage};
+    use crate::compute_dct;
+    use crate::tests::test_pixels;
+
+    #[test]
+    fn test_encode_rgb8_image() {
+        let pixels = test_pixels();
+        let img = RgbImage::from_vec(8, 8, pixels.iter().flatten().copied().collect()).unwrap();
+
+        let expected = compute_dct_iter(pixels.iter(), 8, 8, 4, 3).into_blurhash();
+        assert_eq!(encode_image(&DynamicImage::ImageRgb8(img.clone()), 4, 3), expected);
+        assert_eq!(encode_image(&DynamicImage::ImageRgba8(DynamicImage::ImageRgb8(img).to_rgba8()), 4, 3), expected);
+    }
+
+    #[test]
+    fn test_encode_rgb16_image() {
+        let pixels = test_pixels();
+        let img = ImageBuffer::<Rgb<u16>, _>::from_vec(8, 8, pixels.iter().flatten().map(|&c| c as u16 * 257).collect()).unwrap();
+
+        let expected = compute_dct_iter(pixels.iter(), 8, 8, 4, 3).into_blurhash();
+        assert_eq!(encode_image(&DynamicImage::ImageRgb16(img.clone()), 4, 3), expected);
+        assert_eq!(encode_image(&DynamicImage::ImageRgb32F(DynamicImage::ImageRgb16(img).to_rgb32f()), 4, 3), expected);
+    }
+
+    #[test]
+    fn test_encode_luma8_image() {
+        let img = GrayImage::from_fn(8, 8, |x, y| Luma([(x * 32 + y) as u8]));
+        let pixels: Vec<[u8; 3]> = img.pixels().map(|p| [p[0]; 3]).collect();
+
+        assert_eq!(encode_image(&DynamicImage::ImageLuma8(img.clone()), 3, 3), compute_dct_iter(pixels.iter(), 8, 8, 3, 3).into_blurhash());
+
+        // the luma pixels only transform a single channel
+        assert!(<Luma<u8> as AsLinear>::IS_LUMA && <&LumaA<u16> as AsLinear>::IS_LUMA);
+        let luma: Vec<Luma<u8>> = img.pixels().copied().collect();
+        assert_eq!(compute_dct(&luma, 8, 8, 3, 3).into_blurhash(), compute_dct(&pixels, 8, 8, 3, 3).into_blurhash());
+    }
+
+    #[test]
+    fn test_to_rgba_image() {
+        let dct = crate::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let img = dct.to_rgba_image(32, 24);
+
+        assert_eq!(img.dimensions(), (32, 24));
+        assert_eq!(img.pixels().map(|p| p.0).collect::<Vec<_>>(), dct.to_rgba8(32, 24));
+    }
+}
@@ -0,0 +1,67 @@
+//! Integrations with third-party crates, each one behind its own feature
+
+/// Implement `AsLinear` (and `alpha`) for a pixel type and references to it,
+/// shared by the integrations. Gray pixels also give their `luma` to set
+/// `IS_LUMA`.
+#[allow(unused_macros)]
+macro_rules! impl_as_linear {
+    ($($ty:ty => |$p:ident| $linear:expr, $alpha:expr $(, luma: $luma:expr)?);+ $(;)?) => {
+        $(
+            impl AsLinear for $ty {
+                fn as_linear(&self) -> Linear {
+                    let $p = self;
+                    $linear
+                }
+
+                #[allow(unused_variables)]
+                fn alpha(&self) -> f32 {
+                    let $p = self;
+                    $alpha
+                }
+
+                $(
+                    const IS_LUMA: bool = true;
+
+                    fn as_linear_luma(&self) -> f32 {
+                        let $p = self;
+                        $luma
+                    }
+                )?
+            }
+
+            impl AsLinear for &$ty {
+                fn as_linear(&self) -> Linear {
+                    let $p = *self;
+                    $linear
+                }
+
+                #[allow(unused_variables)]
+                fn alpha(&self) -> f32 {
+                    let $p = *self;
+                    $alpha
+                }
+
+                $(
+                    const IS_LUMA: bool = true;
+
+                    fn as_linear_luma(&self) -> f32 {
+                        let $p = *self;
+                        $luma
+                    }
+                )?
+            }
+        )+
+    };
+}
+
+#[cfg(feature = "image")]
+pub mod image;
+
+#[cfg(feature = "serde")]
+pub mod serde;
+
+#[cfg(feature = "ril")]
+pub mod ril;
+
+#[cfg(feature = "rgb")]
+pub mod rgb;
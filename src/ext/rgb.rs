@@ -0,0 +1,80 @@
+//! Integration with the `rgb` crate (requires the `rgb` feature)
+
+use ::rgb::{RGB8, RGB16, RGBA8, RGBA16};
+use ::rgb::alt::{BGR8, BGRA8, Gray, GrayAlpha};
+use crate::DCTResult;
+use crate::convert::*;
+
+impl_as_linear! {
+    RGB8 => |p| [srgb_to_linear(p.r), srgb_to_linear(p.g), srgb_to_linear(p.b)], 1.;
+    RGBA8 => |p| [srgb_to_linear(p.r), srgb_to_linear(p.g), srgb_to_linear(p.b)], p.a as f32 / 255.;
+    BGR8 => |p| [srgb_to_linear(p.r), srgb_to_linear(p.g), srgb_to_linear(p.b)], 1.;
+    BGRA8 => |p| [srgb_to_linear(p.r), srgb_to_linear(p.g), srgb_to_linear(p.b)], p.a as f32 / 255.;
+    RGB16 => |p| [srgb16_to_linear(p.r), srgb16_to_linear(p.g), srgb16_to_linear(p.b)], 1.;
+    RGBA16 => |p| [srgb16_to_linear(p.r), srgb16_to_linear(p.g), srgb16_to_linear(p.b)], p.a as f32 / 65535.;
+    Gray<u8> => |p| [srgb_to_linear(p.value()); 3], 1., luma: srgb_to_linear(p.value());
+    GrayAlpha<u8> => |p| [srgb_to_linear(p.v); 3], p.a as f32 / 255., luma: srgb_to_linear(p.v);
+    Gray<u16> => |p| [srgb16_to_linear(p.value()); 3], 1., luma: srgb16_to_linear(p.value());
+    GrayAlpha<u16> => |p| [srgb16_to_linear(p.v); 3], p.a as f32 / 65535., luma: srgb16_to_linear(p.v);
+}
+
+impl DCTResult {
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image as a vector of `rgb::RGBA8` pixels (alpha will always be 255).
+    /// Requires the `rgb` feature.
+    pub fn to_rgba8_pixels(&self, width: usize, height: usize) -> Vec<RGBA8> {
+        self.to_image(width, height, |col| RGBA8::new(
+            linear_to_srgb(col[0]),
+            linear_to_srgb(col[1]),
+            linear_to_srgb(col[2]),
+            255
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute_dct;
+    use crate::tests::test_pixels;
+
+    #[test]
+    fn test_encode_rgb_pixels() {
+        let pixels = test_pixels();
+        let expected = compute_dct(&pixels, 8, 8, 4, 3).into_blurhash();
+
+        let rgb: Vec<RGB8> = pixels.iter().map(|&[r, g, b]| RGB8::new(r, g, b)).collect();
+        let rgba: Vec<RGBA8> = pixels.iter().map(|&[r, g, b]| RGBA8::new(r, g, b, 0)).collect();
+        let bgr: Vec<BGR8> = pixels.iter().map(|&[r, g, b]| BGR8 { b, g, r }).collect();
+        let bgra: Vec<BGRA8> = pixels.iter().map(|&[r, g, b]| BGRA8 { b, g, r, a: 0 }).collect();
+        let rgb16: Vec<RGB16> = pixels.iter().map(|&[r, g, b]| RGB16::new(r as u16 * 257, g as u16 * 257, b as u16 * 257)).collect();
+
+        assert_eq!(compute_dct(&rgb, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct(&rgba, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct(&bgr, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct(&bgra, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct(&rgb16, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(AsLinear::alpha(&bgra[0]), 0.);
+    }
+
+    #[test]
+    fn test_encode_gray_pixels() {
+        let pixels: Vec<[u8; 3]> = test_pixels().iter().map(|p| [p[0]; 3]).collect();
+        let expected = compute_dct(&pixels, 8, 8, 4, 3).into_blurhash();
+
+        let gray: Vec<Gray<u8>> = pixels.iter().map(|p| Gray::new(p[0])).collect();
+        let gray_alpha: Vec<GrayAlpha<u16>> = pixels.iter().map(|p| GrayAlpha::new(p[0] as u16 * 257, 0)).collect();
+
+        assert!(<Gray<u8> as AsLinear>::IS_LUMA && <&GrayAlpha<u16> as AsLinear>::IS_LUMA);
+        assert_eq!(compute_dct(&gray, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct(&gray_alpha, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(AsLinear::alpha(&gray_alpha[0]), 0.);
+    }
+
+    #[test]
+    fn test_to_rgba8_pixels() {
+        let dct = crate::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let pixels: Vec<[u8; 4]> = dct.to_rgba8_pixels(32, 24).into_iter().map(|p| [p.r, p.g, p.b, p.a]).collect();
+        assert_eq!(pixels, dct.to_rgba8(32, 24));
+    }
+}
@@ -0,0 +1,70 @@
+//! Integration with the `ril` crate (requires the `ril` feature)
+
+use ::ril::{Image, pixel::{L, Rgb, Rgba}};
+use crate::DCTResult;
+use crate::convert::*;
+
+impl_as_linear! {
+    Rgb => |p| [srgb_to_linear(p.r), srgb_to_linear(p.g), srgb_to_linear(p.b)], 1.;
+    Rgba => |p| [srgb_to_linear(p.r), srgb_to_linear(p.g), srgb_to_linear(p.b)], p.a as f32 / 255.;
+    L => |p| [srgb_to_linear(p.0); 3], 1., luma: srgb_to_linear(p.0);
+}
+
+impl DCTResult {
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image as a `ril::Image<Rgb>` of (width * height) pixels. Requires the
+    /// `ril` feature.
+    ///
+    /// Panics if the width or the height is 0 as `ril` images can't be empty.
+    pub fn to_ril_image(&self, width: u32, height: u32) -> Image<Rgb> {
+        assert!(width > 0 && height > 0, "ril images can't be empty ({width}x{height})");
+        let pixels: Vec<Rgb> = self.to_rgb8(width as usize, height as usize)
+            .into_iter()
+            .map(|[r, g, b]| Rgb::new(r, g, b))
+            .collect();
+        Image::from_pixels(width, pixels)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_dct, compute_dct_iter};
+    use crate::tests::test_pixels;
+
+    #[test]
+    fn test_encode_ril_image() {
+        let pixels = test_pixels();
+        let expected = compute_dct(&pixels, 8, 8, 4, 3).into_blurhash();
+
+        let rgb = Image::from_pixels(8, pixels.iter().map(|&[r, g, b]| Rgb::new(r, g, b)).collect::<Vec<_>>());
+        assert_eq!(compute_dct(&rgb.data, 8, 8, 4, 3).into_blurhash(), expected);
+
+        let rgba = Image::from_pixels(8, pixels.iter().map(|&[r, g, b]| Rgba::new(r, g, b, 0)).collect::<Vec<_>>());
+        assert_eq!(compute_dct(&rgba.data, 8, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(rgba.data[0].alpha(), 0.);
+
+        let luma: Vec<L> = pixels.iter().map(|p| L::new(p[0])).collect();
+        let gray: Vec<[u8; 3]> = pixels.iter().map(|p| [p[0]; 3]).collect();
+        assert_eq!(compute_dct_iter(luma.iter(), 8, 8, 3, 3).into_blurhash(),
+            compute_dct_iter(gray.iter(), 8, 8, 3, 3).into_blurhash());
+        assert!(<L as AsLinear>::IS_LUMA);
+        assert_eq!(compute_dct(&luma, 8, 8, 3, 3).into_blurhash(), compute_dct(&gray, 8, 8, 3, 3).into_blurhash());
+    }
+
+    #[test]
+    fn test_to_ril_image() {
+        let dct = crate::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let img = dct.to_ril_image(32, 24);
+
+        assert_eq!((img.width(), img.height()), (32, 24));
+        let pixels: Vec<[u8; 3]> = img.data.iter().map(|p| [p.r, p.g, p.b]).collect();
+        assert_eq!(pixels, dct.to_rgb8(32, 24));
+    }
+
+    #[test]
+    #[should_panic(expected = "ril images can't be empty (0x24)")]
+    fn test_to_ril_image_empty() {
+        crate::decode("00TSUA", 1.).unwrap().to_ril_image(0, 24);
+    }
+}
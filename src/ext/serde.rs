@@ -0,0 +1,112 @@
+//! Integration with `serde` (requires the `serde` feature)
+//!
+//! [`DCTResult`], [`BlurhashError`] and [`Base83ConversionError`] derive
+//! `Serialize` and `Deserialize`. A [`Blurhash`] is serialized as a string and
+//! malformed blurhashes are rejected when deserializing.
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use crate::{Blurhash, BlurhashError, DCTResult};
+use crate::convert::Factor;
+
+impl Serialize for Blurhash {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Blurhash {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let blurhash = String::deserialize(deserializer)?;
+        Blurhash::new(blurhash).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Unchecked representation of a [`DCTResult`] used to validate the number of
+/// components, the currents and the maximum value of the alternative currents
+/// when deserializing.
+#[derive(Deserialize)]
+#[serde(rename = "DCTResult")]
+pub(crate) struct DCTResultData {
+    ac_max: f32,
+    currents: Vec<Factor>,
+    x_components: usize,
+    y_components: usize
+}
+
+impl TryFrom<DCTResultData> for DCTResult {
+    type Error = String;
+
+    fn try_from(data: DCTResultData) -> Result<Self, Self::Error> {
+        let DCTResultData { ac_max, currents, x_components, y_components } = data;
+        if !(1..=9).contains(&x_components) || !(1..=9).contains(&y_components) {
+            return Err("The number of X and Y components must be between 1 and 9".to_string())
+        }
+
+        if !ac_max.is_finite() || ac_max == 0. {
+            return Err("The maximum value of the alternative currents must be finite and non-zero".to_string())
+        }
+
+        if currents.len() != x_components * y_components {
+            return Err(BlurhashError::InvalidLength.to_string())
+        }
+
+        Ok(DCTResult { ac_max, currents, x_components, y_components })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::base83::Base83ConversionError;
+
+    #[test]
+    fn test_blurhash_json() {
+        let blurhash: Blurhash = serde_json::from_str("\"LlMF%n00%#MwS|WCWEM{R*bbWBbH\"").unwrap();
+        assert_eq!(blurhash.as_str(), "LlMF%n00%#MwS|WCWEM{R*bbWBbH");
+        assert_eq!(serde_json::to_string(&blurhash).unwrap(), "\"LlMF%n00%#MwS|WCWEM{R*bbWBbH\"");
+
+        assert!(serde_json::from_str::<Blurhash>("\"LlMF%n00%#MwS|WCWEM{R*bbWBb\"").is_err());
+        assert!(serde_json::from_str::<Blurhash>("42").is_err());
+    }
+
+    #[test]
+    fn test_dct_result_json() {
+        let dct = crate::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let json = serde_json::to_string(&dct).unwrap();
+
+        let inv: DCTResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(inv.dim(), dct.dim());
+        assert_eq!(inv.currents(), dct.currents());
+        assert_eq!(inv.into_blurhash(), "LlMF%n00%#MwS|WCWEM{R*bbWBbH");
+
+        let invalid = r#"{"ac_max":0.5,"currents":[[0.1,0.2,0.3]],"x_components":2,"y_components":1}"#;
+        assert!(serde_json::from_str::<DCTResult>(invalid).is_err());
+    }
+
+    #[test]
+    fn test_dct_result_json_invalid() {
+        let json = |ac_max: &str, x: usize, y: usize, len: usize| format!(
+            r#"{{"ac_max":{ac_max},"currents":{:?},"x_components":{x},"y_components":{y}}}"#, vec![[0.1f32; 3]; len]);
+        assert!(serde_json::from_str::<DCTResult>(&json("0.5", 3, 2, 6)).is_ok());
+
+        // unsupported number of components
+        for (x, y) in [(10, 1), (1, 10), (0, 1), (1, 0)] {
+            let err = serde_json::from_str::<DCTResult>(&json("0.5", x, y, x * y)).unwrap_err();
+            assert!(err.to_string().contains("must be between 1 and 9"), "{err}");
+        }
+        // the product of the components overflows
+        assert!(serde_json::from_str::<DCTResult>(&json("0.5", usize::MAX, 2, 0)).is_err());
+
+        // invalid maximum value of the alternative currents
+        assert!(serde_json::from_str::<DCTResult>(&json("0.0", 3, 2, 6)).is_err());
+        assert!(serde_json::from_str::<DCTResult>(&json("1e39", 3, 2, 6)).is_err());
+    }
+
+    #[test]
+    fn test_errors_json() {
+        let err = BlurhashError::BadFormat(Base83ConversionError::Overflow);
+        let json = serde_json::to_string(&err).unwrap();
+        assert_eq!(serde_json::from_str::<BlurhashError>(&json).unwrap(), err);
+        assert_eq!(serde_json::to_string(&BlurhashError::InvalidLength).unwrap(), "\"InvalidLength\"");
+    }
+}
@@ -0,0 +1,249 @@
+//! C ABI to use the crate from other languages (requires the `ffi` feature)
+//!
+//! The shared library is built with
+//! `cargo rustc --release --lib --crate-type cdylib --features ffi`, the
+//! matching C header can be found in `include/fast_blurhash.h`. Every function
+//! returns a [`FastBlurhashStatus`] and never panics on invalid arguments.
+
+use std::ffi::{c_char, c_int, CStr};
+use std::slice;
+use crate::{BlurhashError, compute_dct, decode_bytes, encode};
+use crate::base83::Base83ConversionError;
+
+/// Maximum length of a blurhash including the terminating NUL character
+pub const FAST_BLURHASH_MAX_LEN: usize = 1 + 1 + 4 + 2 * (9 * 9 - 1) + 1;
+
+/// Status codes returned by the C functions, mirroring [`BlurhashError`].
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FastBlurhashStatus {
+    /// The function succeeded
+    Ok = 0,
+    /// See [`BlurhashError::InvalidLength`]
+    InvalidLength = 1,
+    /// See [`BlurhashError::InvalidPunch`]
+    InvalidPunch = 2,
+    /// See [`BlurhashError::BadFormat`] and [`Base83ConversionError::InvalidChar`]
+    InvalidChar = 3,
+    /// See [`BlurhashError::BadFormat`] and [`Base83ConversionError::Overflow`]
+    Overflow = 4,
+    /// See [`BlurhashError::UnsupportedMode`]
+    UnsupportedMode = 5,
+    /// A pointer is null, a dimension is zero, the stride is too small, the
+    /// number of channels is not 3 or 4 or the number of components is not
+    /// between 1 and 9.
+    InvalidArgument = 6,
+    /// The output buffer is too small
+    BufferTooSmall = 7,
+}
+
+impl From<BlurhashError> for FastBlurhashStatus {
+    fn from(err: BlurhashError) -> Self {
+        match err {
+            BlurhashError::InvalidLength => Self::InvalidLength,
+            BlurhashError::InvalidPunch => Self::InvalidPunch,
+            BlurhashError::BadFormat(Base83ConversionError::InvalidChar) => Self::InvalidChar,
+            BlurhashError::BadFormat(Base83ConversionError::Overflow) => Self::Overflow,
+            BlurhashError::UnsupportedMode => Self::UnsupportedMode,
+        }
+    }
+}
+
+/// Returns a static NUL-terminated description of a status code. The status is
+/// taken as an `int` so values that are not part of [`FastBlurhashStatus`] are
+/// described as an unknown status.
+#[no_mangle]
+pub extern "C" fn fast_blurhash_strerror(status: c_int) -> *const c_char {
+    use FastBlurhashStatus::*;
+    let msg: &'static [u8] = match status {
+        s if s == Ok as c_int => b"Success\0",
+        s if s == InvalidLength as c_int => b"The extracted length of the blurhash does not match the actual length\0",
+        s if s == InvalidPunch as c_int => b"The punch parameter must be positive and non-zero\0",
+        s if s == InvalidChar as c_int => b"The blurhash contains invalid base83 characters\0",
+        s if s == Overflow as c_int => b"The blurhash contains base83 codes that overflow\0",
+        s if s == UnsupportedMode as c_int => b"The blurhash's number of X or Y components was greater than 9\0",
+        s if s == InvalidArgument as c_int => b"Invalid argument\0",
+        s if s == BufferTooSmall as c_int => b"The output buffer is too small\0",
+        _ => b"Unknown status\0",
+    };
+    msg.as_ptr() as *const c_char
+}
+
+/// Computes the blurhash of an 8-bit sRGB image. `channels` is 3 for RGB
+/// pixels and 4 for RGBA pixels (alpha is ignored), `stride` is the number of
+/// bytes between the start of two rows. The NUL-terminated blurhash is written
+/// to `out` which must be able to hold `out_len` bytes
+/// ([`FAST_BLURHASH_MAX_LEN`] is always enough).
+///
+/// # Safety
+///
+/// `pixels` must point to at least `stride * (height - 1) + width * channels`
+/// readable bytes and `out` to `out_len` writable bytes.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub unsafe extern "C" fn fast_blurhash_encode(
+    pixels: *const u8, width: usize, height: usize, stride: usize, channels: usize,
+    x_components: usize, y_components: usize, out: *mut c_char, out_len: usize
+) -> FastBlurhashStatus {
+    let Some((row_len, len)) = image_len(width, height, stride, channels) else {
+        return FastBlurhashStatus::InvalidArgument
+    };
+    if pixels.is_null() || out.is_null()
+        || !(1..=9).contains(&x_components) || !(1..=9).contains(&y_components) {
+        return FastBlurhashStatus::InvalidArgument
+    }
+
+    if out_len < 1 + 1 + 4 + 2 * (x_components * y_components - 1) + 1 {
+        return FastBlurhashStatus::BufferTooSmall
+    }
+
+    let data = slice::from_raw_parts(pixels, len);
+    let rows = data.chunks(stride).map(|row| &row[..row_len]);
+
+    let pixels: Vec<[u8; 3]> = rows
+        .flat_map(|row| row.chunks_exact(channels))
+        .map(|p| [p[0], p[1], p[2]])
+        .collect();
+    let dct = compute_dct(&pixels, width, height, x_components, y_components);
+
+    let blurhash = encode(&dct);
+    let out = slice::from_raw_parts_mut(out as *mut u8, out_len);
+    out[..blurhash.len()].copy_from_slice(blurhash.as_bytes());
+    out[blurhash.len()] = 0;
+
+    FastBlurhashStatus::Ok
+}
+
+/// Decodes a NUL-terminated blurhash into an 8-bit sRGB image of (width * height)
+/// pixels provided by the caller. `channels` is 3 for RGB pixels and 4 for RGBA
+/// pixels (alpha will always be 255), `stride` is the number of bytes between the
+/// start of two rows.
+///
+/// # Safety
+///
+/// `blurhash` must be a valid NUL-terminated string and `out` must point to
+/// at least `stride * (height - 1) + width * channels` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn fast_blurhash_decode(
+    blurhash: *const c_char, punch: f32, out: *mut u8,
+    width: usize, height: usize, stride: usize, channels: usize
+) -> FastBlurhashStatus {
+    let Some((row_len, len)) = image_len(width, height, stride, channels) else {
+        return FastBlurhashStatus::InvalidArgument
+    };
+    if blurhash.is_null() || out.is_null() {
+        return FastBlurhashStatus::InvalidArgument
+    }
+
+    let dct = match decode_bytes(CStr::from_ptr(blurhash).to_bytes(), punch) {
+        Ok(dct) => dct,
+        Err(err) => return err.into()
+    };
+
+    let out = slice::from_raw_parts_mut(out, len);
+    let pixels = dct.to_rgba8(width, height);
+
+    for (row, pixels) in out.chunks_mut(stride).zip(pixels.chunks_exact(width)) {
+        for (dst, src) in row[..row_len].chunks_exact_mut(channels).zip(pixels) {
+            dst.copy_from_slice(&src[..channels]);
+        }
+    }
+
+    FastBlurhashStatus::Ok
+}
+
+/// Returns the number of bytes of a row of pixels and of the whole image, or
+/// None if the dimensions are invalid or the sizes overflow.
+fn image_len(width: usize, height: usize, stride: usize, channels: usize) -> Option<(usize, usize)> {
+    if width == 0 || height == 0 || !(3..=4).contains(&channels) {
+        return None
+    }
+
+    let row_len = width.checked_mul(channels)?;
+    if stride < row_len {
+        return None
+    }
+    Some((row_len, stride.checked_mul(height - 1)?.checked_add(row_len)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_with_stride() {
+        let image: [[u8; 3]; 4] = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        let mut padded = [7u8; 2 * 8];
+        padded[0..6].copy_from_slice(&[255, 0, 0, 0, 255, 0]);
+        padded[8..14].copy_from_slice(&[0, 0, 255, 255, 255, 255]);
+
+        let mut out = [0 as c_char; FAST_BLURHASH_MAX_LEN];
+        let status = unsafe { fast_blurhash_encode(padded.as_ptr(), 2, 2, 8, 3, 3, 3, out.as_mut_ptr(), out.len()) };
+        assert_eq!(status, FastBlurhashStatus::Ok);
+
+        let blurhash = unsafe { CStr::from_ptr(out.as_ptr()) }.to_str().unwrap();
+        assert_eq!(blurhash, encode(&compute_dct(&image, 2, 2, 3, 3)));
+    }
+
+    #[test]
+    fn test_encode_invalid() {
+        let image = [0u8; 12];
+        let mut out = [0 as c_char; 8];
+        unsafe {
+            assert_eq!(fast_blurhash_encode(image.as_ptr(), 2, 2, 6, 3, 3, 3, out.as_mut_ptr(), out.len()),
+                FastBlurhashStatus::BufferTooSmall);
+            assert_eq!(fast_blurhash_encode(image.as_ptr(), 2, 2, 6, 3, 10, 3, out.as_mut_ptr(), out.len()),
+                FastBlurhashStatus::InvalidArgument);
+            assert_eq!(fast_blurhash_encode(image.as_ptr(), 2, 2, 5, 3, 1, 1, out.as_mut_ptr(), out.len()),
+                FastBlurhashStatus::InvalidArgument);
+            assert_eq!(fast_blurhash_encode(std::ptr::null(), 2, 2, 6, 3, 1, 1, out.as_mut_ptr(), out.len()),
+                FastBlurhashStatus::InvalidArgument);
+
+            // the sizes overflow
+            assert_eq!(fast_blurhash_encode(image.as_ptr(), usize::MAX / 2, 2, usize::MAX, 3, 1, 1, out.as_mut_ptr(), out.len()),
+                FastBlurhashStatus::InvalidArgument);
+            assert_eq!(fast_blurhash_encode(image.as_ptr(), 2, usize::MAX, usize::MAX / 2, 3, 1, 1, out.as_mut_ptr(), out.len()),
+                FastBlurhashStatus::InvalidArgument);
+        }
+    }
+
+    #[test]
+    fn test_decode_with_stride() {
+        let blurhash = b"LlMF%n00%#MwS|WCWEM{R*bbWBbH\0";
+        let mut out = [0u8; 3 * 20];
+        let status = unsafe { fast_blurhash_decode(blurhash.as_ptr() as *const c_char, 1., out.as_mut_ptr(), 4, 3, 20, 4) };
+        assert_eq!(status, FastBlurhashStatus::Ok);
+
+        let expected = crate::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap().to_rgba8(4, 3);
+        for (y, row) in out.chunks(20).enumerate() {
+            assert_eq!(&row[..16], expected[y * 4..(y + 1) * 4].concat());
+            assert_eq!(&row[16..], [0; 4]);
+        }
+    }
+
+    #[test]
+    fn test_decode_errors() {
+        let mut out = [0u8; 16];
+        unsafe {
+            assert_eq!(fast_blurhash_decode(b"LlMF\0".as_ptr() as *const c_char, 1., out.as_mut_ptr(), 2, 2, 6, 3),
+                FastBlurhashStatus::InvalidLength);
+            assert_eq!(fast_blurhash_decode(b"00TSUA\0".as_ptr() as *const c_char, 0., out.as_mut_ptr(), 2, 2, 6, 3),
+                FastBlurhashStatus::InvalidPunch);
+            // not valid UTF-8
+            assert_eq!(fast_blurhash_decode(b"\xB00TSUA\0".as_ptr() as *const c_char, 1., out.as_mut_ptr(), 2, 2, 6, 3),
+                FastBlurhashStatus::InvalidChar);
+            assert_eq!(fast_blurhash_decode(b"\xC2\xB0TSUA\0".as_ptr() as *const c_char, 1., out.as_mut_ptr(), 2, 2, 6, 3),
+                FastBlurhashStatus::InvalidChar);
+            assert_eq!(fast_blurhash_decode(b"00TSUA\0".as_ptr() as *const c_char, 1., out.as_mut_ptr(), 2, 2, 6, 5),
+                FastBlurhashStatus::InvalidArgument);
+            assert_eq!(fast_blurhash_decode(b"00TSUA\0".as_ptr() as *const c_char, 1., out.as_mut_ptr(), 2, usize::MAX, usize::MAX / 2, 3),
+                FastBlurhashStatus::InvalidArgument);
+            assert_eq!(fast_blurhash_decode(b"00TSUA\0".as_ptr() as *const c_char, 1., out.as_mut_ptr(), usize::MAX, 2, usize::MAX, 4),
+                FastBlurhashStatus::InvalidArgument);
+            assert_eq!(CStr::from_ptr(fast_blurhash_strerror(FastBlurhashStatus::InvalidPunch as c_int)).to_str(),
+                Ok("The punch parameter must be positive and non-zero"));
+            assert_eq!(CStr::from_ptr(fast_blurhash_strerror(42)).to_str(), Ok("Unknown status"));
+            assert_eq!(CStr::from_ptr(fast_blurhash_strerror(-1)).to_str(), Ok("Unknown status"));
+        }
+    }
+}
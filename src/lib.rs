@@ -100,15 +100,80 @@
 //! let image: Vec<Vec<Color>> = todo!("Load the image");
 //! let blurhash = compute_dct_iter(image.iter().flatten(), width, height, 3, 4).into_blurhash();
 //! ```
+//!
+//! ## Transparency
+//!
+//! The BlurHash format does not support transparency. This crate provides an
+//! opt-in and **non-standard** extension that encodes the alpha plane after
+//! the color components, check out the [`alpha`] module.
+//!
+//! [`alpha`]: alpha/index.html
+//!
+//! ## Features
+//!
+//! Integrations with other crates are available behind features:
+//! - `image`: implements [`AsLinear`] for the pixels of the `image` crate and
+//!   adds [`encode_image`] and `DCTResult::to_rgba_image`.
+//! - `ril`: implements [`AsLinear`] for the `Rgb`, `Rgba` and `L` pixels of
+//!   the `ril` crate and adds `DCTResult::to_ril_image`.
+//! - `rgb`: implements [`AsLinear`] for the `RGB8`, `RGBA8`, `BGR8`, `BGRA8`,
+//!   `RGB16`, `RGBA16`, `Gray` and `GrayAlpha` pixels of the `rgb` crate and
+//!   adds `DCTResult::to_rgba8_pixels`.
+//! - `half`: implements [`AsLinear`] for `[f16; 3]` and `[f16; 4]` pixels in
+//!   linear space.
+//! - `serde`: implements `Serialize` and `Deserialize` for [`DCTResult`], the
+//!   errors and [`Blurhash`] which rejects malformed blurhashes.
+//! - `ffi`: exports a C ABI from the `cdylib`, check out the `ffi` module and
+//!   the `include/fast_blurhash.h` header.
+//! - `wasm`: WebAssembly bindings using `wasm-bindgen`, check out the `wasm`
+//!   module.
+//! - `python`: Python extension module using `pyo3` working with numpy
+//!   arrays, build it with `maturin develop --release`.
+//! - `node`: Node.js native addon using `napi-rs` with async `encode` and
+//!   `decode` functions, check out the `node` module.
+//! - `cli`: builds the `blurhash` command-line tool to encode, decode and
+//!   inspect blurhashes (`cargo install fast-blurhash --features cli`).
+//!
+//! [`AsLinear`]: convert/trait.AsLinear.html
+//! [`encode_image`]: fn.encode_image.html
+//! [`DCTResult`]: struct.DCTResult.html
+//! [`Blurhash`]: struct.Blurhash.html
 
 pub mod base83;
 pub mod convert;
+pub mod alpha;
+pub mod png;
+pub mod options;
+pub mod packed;
+pub mod yuv;
+pub mod transfer;
+mod css;
+mod svg;
+mod blurhash;
+mod ext;
+
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "node")]
+pub mod node;
+
+pub use blurhash::Blurhash;
+pub use options::{AlphaMode, ColorSpace, EncodeOptions, ToneMap};
+
+#[cfg(feature = "image")]
+pub use ext::image::encode_image;
 
 use std::f32::consts::PI;
 use convert::*;
 use base83::encode_fixed_to;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum BlurhashError {
     /// Occurs when the provided blurhash's lenght is not the same as the expected
     /// length that was extracted from the 'header' (first char) of the blurhash
@@ -143,6 +208,8 @@ impl From<base83::Base83ConversionError> for BlurhashError {
 /// with a specific number of X and Y components. It stores the frequency and
 /// location of colors within the image.
 #[derive(Default, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "ext::serde::DCTResultData"))]
 pub struct DCTResult {
     /// The absolute maximum value of each channel in the alternative currents
     ac_max: f32,
@@ -176,6 +243,12 @@ impl DCTResult {
     /// image. This function allocates a vector of (width * height) pixels in
     /// the linear space.
     pub fn to_image<T>(&self, width: usize, height: usize, convert: fn(Linear) -> T) -> Vec<T> {
+        self.map_pixels(width, height, convert)
+    }
+
+    /// Same as [`to_image`](Self::to_image) but the conversion can capture its
+    /// environment.
+    pub(crate) fn map_pixels<T>(&self, width: usize, height: usize, mut convert: impl FnMut(Linear) -> T) -> Vec<T> {
         let mut pixels = Vec::with_capacity(width * height);
 
         for y in 0..height {
@@ -197,39 +270,40 @@ impl DCTResult {
         pixels
     }
 
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image. This function allocates a vector of (width * height) pixels of
+    /// any type implementing [`FromLinear`].
+    ///
+    /// #### Example
+    /// ```
+    /// use fast_blurhash::decode;
+    ///
+    /// let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+    /// let image = dct.render::<[u16; 3]>(32, 32);
+    /// ```
+    pub fn render<T: FromLinear>(&self, width: usize, height: usize) -> Vec<T> {
+        self.to_image(width, height, T::from_linear)
+    }
+
     /// Generate an image from this DCT Result to recreate (sort of) the original
     /// image. This function allocates a vector of (width * height) pixels in
     /// the sRGB space as in [RR, GG, BB].
     pub fn to_rgb8(&self, width: usize, height: usize) -> Vec<[u8; 3]> {
-        self.to_image(width, height, |col| [
-            linear_to_srgb(col[0]),
-            linear_to_srgb(col[1]),
-            linear_to_srgb(col[2]),
-        ])
+        self.render(width, height)
     }
 
     /// Generate an image from this DCT Result to recreate (sort of) the original
     /// image. This function allocates a vector of (width * height) pixels in
     /// the sRGB space as in [RR, GG, BB, AA]. (alpha will always be 255).
     pub fn to_rgba8(&self, width: usize, height: usize) -> Vec<[u8; 4]> {
-        self.to_image(width, height, |col| [
-            linear_to_srgb(col[0]),
-            linear_to_srgb(col[1]),
-            linear_to_srgb(col[2]),
-            255
-        ])
+        self.render(width, height)
     }
 
     /// Generate an image from this DCT Result to recreate (sort of) the original
     /// image. This function allocates a vector of (width * height) u32 in
     /// the sRGB space as in AARRGGBB in hex (alpha will always be 255).
     pub fn to_rgba(&self, width: usize, height: usize) -> Vec<u32> {
-        self.to_image(width, height, |col|
-            ((linear_to_srgb(col[2]) as u32) <<  0) |
-            ((linear_to_srgb(col[1]) as u32) <<  8) |
-            ((linear_to_srgb(col[0]) as u32) << 16) |
-            ((255                    as u32) << 24)
-        )
+        self.render(width, height)
     }
 
     /// Retrieve the currents of the DCT. The returned array is
@@ -264,10 +338,41 @@ impl DCTResult {
         self.y_components
     }
 
+    /// Retrieve the absolute maximum value of each channel in the alternative
+    /// currents
+    pub fn ac_max(&self) -> f32 {
+        self.ac_max
+    }
+
     // Retrive the dimension (x_components, y_components) of the computed DCT
     pub fn dim(&self) -> (usize, usize) {
         (self.x_components, self.y_components)
     }
+
+    /// Sample the colors at the center of each cell of a (cols * rows) grid
+    /// spanning the whole image. The colors are stored in row-major order and
+    /// encoded as RRGGBB.
+    fn sample_cells(&self, cols: usize, rows: usize) -> Vec<u32> {
+        let mut cells = Vec::with_capacity(cols * rows);
+
+        for row in 0..rows {
+            let percent_y = (row as f32 + 0.5) / rows as f32;
+            for col in 0..cols {
+                let percent_x = (col as f32 + 0.5) / cols as f32;
+
+                let mut color = inv_multiply_basis(self.x_components, self.y_components,
+                    percent_x, percent_y, &self.currents);
+
+                color[0] = color[0].clamp(0., 1.);
+                color[1] = color[1].clamp(0., 1.);
+                color[2] = color[2].clamp(0., 1.);
+
+                cells.push(to_rgb(color));
+            }
+        }
+
+        cells
+    }
 }
 
 /// Compute the blurhash string from the DCT result using the wolt/blurhash format.
@@ -303,40 +408,70 @@ pub fn encode(dct: &DCTResult) -> String {
 /// disposition) using the wolt/blurhash format. This function may allocate up to a
 /// vector of length 81 contained in the DCTResult struct.
 pub fn decode(blurhash: &str, punch: f32) -> Result<DCTResult, BlurhashError> {
+    decode_bytes(blurhash.as_bytes(), punch)
+}
+
+/// Same as [`decode`] on the bytes of the blurhash, non-ascii bytes are
+/// reported as invalid base83 characters.
+pub(crate) fn decode_bytes(blurhash: &[u8], punch: f32) -> Result<DCTResult, BlurhashError> {
     if punch <= 0. {
         return Err(BlurhashError::InvalidPunch)
     }
 
-    if blurhash.is_empty() {
-        return Err(BlurhashError::InvalidLength)
-    }
-    let total = base83::decode(&blurhash[..1])? as usize;
-    let (x_components, y_components) = ((total % 9) + 1, (total / 9) + 1);
-
-    if x_components > 9 || y_components > 9 {
-        return Err(BlurhashError::UnsupportedMode)
-    }
+    let (x_components, y_components) = decode_header(blurhash)?;
 
     let current_count = x_components * y_components;
     if blurhash.len() != 1 + 1 + 4 + 2 * (current_count - 1) {
         return Err(BlurhashError::InvalidLength)
     }
 
-    let ac_max = base83::decode(&blurhash[1..2])? + 1;
+    let ac_max = base83::decode_bytes(&blurhash[1..2])? + 1;
     let ac_max = ((ac_max as f32) / 166.) * punch;
 
     let mut currents = Vec::with_capacity(current_count);
-    currents.push(decode_dc(base83::decode(&blurhash[2..6])?));
+    currents.push(decode_dc(base83::decode_bytes(&blurhash[2..6])?));
 
     for i in 1..current_count {
         let idx = (i - 1) * 2 + 6;
-        let ac = base83::decode(&blurhash[idx..(idx + 2)])?;
+        let ac = base83::decode_bytes(&blurhash[idx..(idx + 2)])?;
         currents.push(decode_ac(ac, ac_max));
     }
 
     Ok(DCTResult { ac_max, currents, x_components, y_components })
 }
 
+/// Decode the number of X and Y components stored in the first character of a
+/// blurhash.
+pub(crate) fn decode_header(blurhash: &[u8]) -> Result<(usize, usize), BlurhashError> {
+    if blurhash.is_empty() {
+        return Err(BlurhashError::InvalidLength)
+    }
+    let total = base83::decode_bytes(&blurhash[..1])? as usize;
+    let (x_components, y_components) = ((total % 9) + 1, (total / 9) + 1);
+
+    if x_components > 9 || y_components > 9 {
+        return Err(BlurhashError::UnsupportedMode)
+    }
+
+    Ok((x_components, y_components))
+}
+
+/// Retrieve the average color (the DC) of a blurhash in the sRGB space as in
+/// [RR, GG, BB] without decoding the alternative currents. The header and the
+/// length of the blurhash are validated but the ACs are not. This function does
+/// not allocate.
+pub fn average_color(blurhash: &str) -> Result<[u8; 3], BlurhashError> {
+    let blurhash = blurhash.as_bytes();
+    let (x_components, y_components) = decode_header(blurhash)?;
+
+    if blurhash.len() != 1 + 1 + 4 + 2 * (x_components * y_components - 1) {
+        return Err(BlurhashError::InvalidLength)
+    }
+
+    let [_, r, g, b] = base83::decode_bytes(&blurhash[2..6])?.to_be_bytes();
+    Ok([r, g, b])
+}
+
 /// Compute the Discrete Cosine Transform on an image in linear space. The iterator
 /// must be long enough (it must have at least width * height items).
 ///
@@ -347,11 +482,25 @@ pub fn decode(blurhash: &str, punch: f32) -> Result<DCTResult, BlurhashError> {
 /// Note: To generate a valid blurhash, the number of X or/and Y components
 /// must be between 1 and 9. This is a limitation of the encoding scheme.
 pub fn compute_dct_iter<T: AsLinear>(image: impl Iterator<Item = T>, width: usize, height: usize, x_components: usize, y_components: usize) -> DCTResult {
+    compute_dct_iter_with(image, width, height, x_components, y_components, &EncodeOptions::default())
+}
+
+/// Same as [`compute_dct_iter`] but with custom [`EncodeOptions`], for example
+/// to tone-map high dynamic range images.
+///
+/// [`EncodeOptions`]: options/struct.EncodeOptions.html
+pub fn compute_dct_iter_with<T: AsLinear>(image: impl Iterator<Item = T>, width: usize, height: usize, x_components: usize, y_components: usize, options: &EncodeOptions) -> DCTResult {
     let mut currents: Vec<Factor> = vec![[0., 0., 0.]; x_components * y_components];
 
+    let weighted = options.alpha == AlphaMode::Weighted;
+    let mut weight = 0f64;
+
     let total = width * height;
     for (i, pixel) in image.take(total).enumerate() {
-        let col = pixel.as_linear();
+        let col = options.linear_color(&pixel);
+        if weighted {
+            weight += pixel.alpha() as f64;
+        }
 
         let p = i as f32 / width as f32;
         let percent_y = p / height as f32;
@@ -360,7 +509,11 @@ pub fn compute_dct_iter<T: AsLinear>(image: impl Iterator<Item = T>, width: usiz
         multiply_basis(x_components, y_components, percent_x, percent_y, &col, &mut currents);
     }
 
-    let ac_max = normalize_and_max(&mut currents, total);
+    let ac_max = if weighted {
+        normalize_weighted_and_max(&mut currents, total_weight(weight))
+    } else {
+        normalize_and_max(&mut currents, total)
+    };
 
     DCTResult { ac_max, currents, x_components, y_components }
 }
@@ -372,23 +525,89 @@ pub fn compute_dct_iter<T: AsLinear>(image: impl Iterator<Item = T>, width: usiz
 /// vector of (x_components * y_components * 3) floats, the process may be a
 /// little slow depending on the size of the input image.
 ///
+/// Gray pixels (such as `u8` or `u16`) only transform a single channel which
+/// is faster and produces the same result.
+///
 /// Note: To generate a valid blurhash, the number of X or/and Y components
 /// must be between 1 and 9. This is a limitation of the encoding scheme.
 pub fn compute_dct<T: AsLinear>(image: &[T], width: usize, height: usize, x_components: usize, y_components: usize) -> DCTResult {
+    compute_dct_with(image, width, height, x_components, y_components, &EncodeOptions::default())
+}
+
+/// Same as [`compute_dct`] but with custom [`EncodeOptions`], for example to
+/// tone-map high dynamic range images.
+///
+/// [`EncodeOptions`]: options/struct.EncodeOptions.html
+pub fn compute_dct_with<T: AsLinear>(image: &[T], width: usize, height: usize, x_components: usize, y_components: usize, options: &EncodeOptions) -> DCTResult {
     assert!(image.len() >= width * height);
+    if T::IS_LUMA && options.keeps_gray() {
+        return compute_dct_luma(image, width, height, x_components, y_components, options)
+    }
+
     let mut currents: Vec<Factor> = vec![[0., 0., 0.]; x_components * y_components];
+    let weighted = options.alpha == AlphaMode::Weighted;
+    let mut weight = 0f64;
 
     for y in 0..height {
         let percent_y = y as f32 / height as f32;
         for x in 0..width {
             let percent_x = x as f32 / width as f32;
 
-            let col = image[y * width + x].as_linear();
+            let pixel = &image[y * width + x];
+            let col = options.linear_color(pixel);
+            if weighted {
+                weight += pixel.alpha() as f64;
+            }
             multiply_basis(x_components, y_components, percent_x, percent_y, &col, &mut currents);
         }
     }
 
-    let ac_max = normalize_and_max(&mut currents, width * height);
+    let ac_max = if weighted {
+        normalize_weighted_and_max(&mut currents, total_weight(weight))
+    } else {
+        normalize_and_max(&mut currents, width * height)
+    };
+
+    DCTResult { ac_max, currents, x_components, y_components }
+}
+
+/// Total weight of an alpha-weighted image, a fully transparent image has no
+/// contribution at all so any non-zero weight gives the same (empty) currents.
+fn total_weight(weight: f64) -> f32 {
+    if weight > 0. { weight as f32 } else { 1. }
+}
+
+/// Single channel version of [`compute_dct_with`] for gray pixels (see
+/// [`AsLinear::IS_LUMA`]): only one channel is transformed and then replicated
+/// to the three channels of the result, which produces the same blurhash.
+///
+/// [`AsLinear::IS_LUMA`]: convert/trait.AsLinear.html#associatedconstant.IS_LUMA
+fn compute_dct_luma<T: AsLinear>(image: &[T], width: usize, height: usize, x_components: usize, y_components: usize, options: &EncodeOptions) -> DCTResult {
+    let mut luma_currents: Vec<f32> = vec![0.; x_components * y_components];
+    let weighted = options.alpha == AlphaMode::Weighted;
+    let mut weight = 0f64;
+    let plain = *options == EncodeOptions::default();
+
+    for y in 0..height {
+        let percent_y = y as f32 / height as f32;
+        for x in 0..width {
+            let percent_x = x as f32 / width as f32;
+
+            let pixel = &image[y * width + x];
+            let luma = if plain { pixel.as_linear_luma() } else { options.linear_color(pixel)[0] };
+            if weighted {
+                weight += pixel.alpha() as f64;
+            }
+            multiply_basis_luma(x_components, y_components, percent_x, percent_y, luma, &mut luma_currents);
+        }
+    }
+
+    let mut currents: Vec<Factor> = luma_currents.into_iter().map(|f| [f; 3]).collect();
+    let ac_max = if weighted {
+        normalize_weighted_and_max(&mut currents, total_weight(weight))
+    } else {
+        normalize_and_max(&mut currents, width * height)
+    };
 
     DCTResult { ac_max, currents, x_components, y_components }
 }
@@ -415,6 +634,19 @@ pub fn multiply_basis(x_comps: usize, y_comps: usize, x: f32, y: f32, col: &[f32
     }
 }
 
+/// Single channel version of [`multiply_basis`] used for gray pixels
+#[inline]
+fn multiply_basis_luma(x_comps: usize, y_comps: usize, x: f32, y: f32, luma: f32, currents: &mut [f32]) {
+    for comp_y in 0..y_comps {
+        let base_y = (PI * comp_y as f32 * y).cos();
+
+        for comp_x in 0..x_comps {
+            let base_x = (PI * comp_x as f32 * x).cos();
+            currents[comp_y * x_comps + comp_x] += base_y * base_x * luma;
+        }
+    }
+}
+
 /// Compute an iteration of the inverse DCT for every component on the pixel (x, y)
 /// and stores the color of that pixel into `col`. Note that the currents slice must
 /// be long enough (x_comps * y_comps).
@@ -445,8 +677,17 @@ pub fn inv_multiply_basis(x_comps: usize, y_comps: usize, x: f32, y: f32, curren
 /// currents must have one or more items and len is the total number of pixels
 /// of the image (width * height).
 pub fn normalize_and_max(currents: &mut [Factor], len: usize) -> f32 {
-    let len = len as f32;
-    let norm = 1. / len; // Normalisation for DC is 1
+    normalize_weighted_and_max(currents, len as f32)
+}
+
+/// Same as [`normalize_and_max`] but the currents are normalized by the total
+/// weight of the pixels instead of their number. This is used by the
+/// alpha-weighted encoding (see [`AlphaMode::Weighted`]) where the total
+/// weight is the sum of the alpha values of the pixels.
+///
+/// [`AlphaMode::Weighted`]: options/enum.AlphaMode.html#variant.Weighted
+pub fn normalize_weighted_and_max(currents: &mut [Factor], total_weight: f32) -> f32 {
+    let norm = 1. / total_weight; // Normalisation for DC is 1
     let f = &mut currents[0];
     f[0] *= norm;
     f[1] *= norm;
@@ -457,7 +698,7 @@ pub fn normalize_and_max(currents: &mut [Factor], len: usize) -> f32 {
     }
 
     let mut ac_max = 0f32;
-    let norm = 2. / len; // Normalisation for ACs is 2
+    let norm = 2. / total_weight; // Normalisation for ACs is 2
     for f in currents.iter_mut().skip(1).flatten() {
         *f *= norm;
         ac_max = ac_max.max(f.abs());
@@ -470,6 +711,11 @@ pub fn normalize_and_max(currents: &mut [Factor], len: usize) -> f32 {
 mod tests {
     use super::*;
 
+    /// An 8x8 gradient shared by the tests of the pixel formats
+    pub(crate) fn test_pixels() -> Vec<[u8; 3]> {
+        (0..64u32).map(|i| [(i * 4) as u8, (255 - i * 3) as u8, ((i % 8) * 32) as u8]).collect()
+    }
+
     #[test]
     fn test_multiply_basis() {
         let width: usize = 4;
@@ -627,15 +873,218 @@ mod tests {
         }
     }
 
-    use ril::prelude::Image;
+    #[test]
+    fn test_decode_non_ascii() {
+        let invalid_char = BlurhashError::BadFormat(base83::Base83ConversionError::InvalidChar);
+        // the length in bytes matches the header but the characters are not ascii
+        for blurhash in ["°0TSUA", "00TS°", "L°F%n00%#MwS|WCWEM{R*bbWBbH", "LlMF%n00%#MwS|WCWEM{R*bbWB°"] {
+            assert_eq!(decode(blurhash, 1.).unwrap_err(), invalid_char, "{blurhash}");
+        }
+        assert_eq!(decode("0°TSUA", 1.).unwrap_err(), BlurhashError::InvalidLength);
+    }
+
+    #[test]
+    fn test_compute_dct_luma() {
+        let luma: Vec<u8> = (0..48u32).map(|i| (i * 5 + (i % 6) * 17) as u8).collect();
+        let rgb: Vec<[u8; 3]> = luma.iter().map(|&l| [l; 3]).collect();
+        let parts = |dct: DCTResult| (*dct.dc(), dct.acs().to_vec(), dct.ac_max());
+        let expected = parts(compute_dct(&rgb, 6, 8, 4, 3));
+
+        assert!(u8::IS_LUMA && <&[u8; 2]>::IS_LUMA && !<[u8; 3]>::IS_LUMA);
+        assert_eq!(parts(compute_dct(&luma, 6, 8, 4, 3)), expected);
+        assert_eq!(parts(compute_dct_iter(luma.iter(), 6, 8, 4, 3)), parts(compute_dct_iter(rgb.iter(), 6, 8, 4, 3)));
+
+        let luma_alpha: Vec<[u8; 2]> = luma.iter().map(|&l| [l, 0]).collect();
+        assert_eq!(parts(compute_dct(&luma_alpha, 6, 8, 4, 3)), expected);
+        assert_eq!(luma_alpha[0].alpha(), 0.);
+
+        let luma16: Vec<u16> = luma.iter().map(|&l| l as u16 * 257).collect();
+        assert_eq!(compute_dct(&luma16, 6, 8, 4, 3).into_blurhash(), compute_dct(&rgb, 6, 8, 4, 3).into_blurhash());
+    }
+
+    #[test]
+    fn test_compute_dct_luma_with_options() {
+        let luma: Vec<[u8; 2]> = (0..48u32).map(|i| [(i * 5 + (i % 6) * 17) as u8, (i * 11 % 256) as u8]).collect();
+        let rgba: Vec<[u8; 4]> = luma.iter().map(|&[l, a]| [l, l, l, a]).collect();
+        let parts = |dct: DCTResult| (*dct.dc(), dct.acs().to_vec(), dct.ac_max());
+
+        let gray = [0.5; 3];
+        for options in [
+            EncodeOptions { tone_map: ToneMap::Reinhard, alpha: AlphaMode::Weighted, ..Default::default() },
+            EncodeOptions { alpha: AlphaMode::Composite(gray), color_space: ColorSpace::AdobeRgb, ..Default::default() },
+            EncodeOptions { alpha: AlphaMode::ReplaceTransparent(gray), tone_map: ToneMap::Clamp, ..Default::default() },
+        ] {
+            // the rows of the color space matrices sum to 1 so gray stays gray,
+            // but the rounding may differ slightly between the channels: the
+            // blurhashes are compared instead of the factors
+            assert!(options.keeps_gray());
+            assert_eq!(compute_dct_with(&luma, 6, 8, 4, 3, &options).into_blurhash(),
+                compute_dct_with(&rgba, 6, 8, 4, 3, &options).into_blurhash(), "{options:?}");
+        }
+
+        // a colored background does not keep the pixels gray
+        let options = EncodeOptions { alpha: AlphaMode::Composite([1., 0., 0.]), ..Default::default() };
+        assert!(!options.keeps_gray());
+        let dct = compute_dct_with(&luma, 6, 8, 4, 3, &options);
+        assert_eq!(parts(dct.clone()), parts(compute_dct_with(&rgba, 6, 8, 4, 3, &options)));
+        assert!(dct.dc()[0] > dct.dc()[1]);
+    }
 
-    impl AsLinear for &ril::pixel::Rgb {
-        fn as_linear(&self) -> Linear {
-            [srgb_to_linear(self.r), srgb_to_linear(self.g), srgb_to_linear(self.b)]
+    #[test]
+    fn test_compute_dct_wide_inputs() {
+        let rgb: Vec<[u8; 3]> = (0..48u32).map(|i| [(i * 5) as u8, (255 - i * 3) as u8, ((i % 6) * 40) as u8]).collect();
+        let expected = compute_dct(&rgb, 6, 8, 4, 3).into_blurhash();
+
+        let rgb16: Vec<[u16; 3]> = rgb.iter().map(|p| p.map(|c| c as u16 * 257)).collect();
+        let rgba16: Vec<[u16; 4]> = rgb16.iter().map(|&[r, g, b]| [r, g, b, 0]).collect();
+        assert_eq!(compute_dct(&rgb16, 6, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct(&rgba16, 6, 8, 4, 3).into_blurhash(), expected);
+
+        let linear: Vec<[f32; 3]> = rgb.iter().map(|p| p.as_linear()).collect();
+        let linear_alpha: Vec<[f32; 4]> = linear.iter().map(|&[r, g, b]| [r, g, b, 0.5]).collect();
+        assert_eq!(compute_dct(&linear, 6, 8, 4, 3).into_blurhash(), expected);
+        assert_eq!(compute_dct_iter(linear_alpha.iter(), 6, 8, 4, 3).into_blurhash(),
+            compute_dct_iter(rgb.iter(), 6, 8, 4, 3).into_blurhash());
+
+        #[cfg(feature = "half")]
+        {
+            let half: Vec<[half::f16; 3]> = linear.iter().map(|p| p.map(half::f16::from_f32)).collect();
+            let dct = compute_dct(&half, 6, 8, 4, 3);
+            for (a, b) in dct.acs().iter().flatten().zip(compute_dct(&linear, 6, 8, 4, 3).acs().iter().flatten()) {
+                assert!((a - b).abs() < 1e-3);
+            }
         }
     }
 
     #[test]
+    fn test_compute_dct_tone_mapped() {
+        let mut hdr: Vec<[f32; 3]> = (0..64u32).map(|i| [(i % 8) as f32 / 8., 0.5, (i / 8) as f32 / 8.]).collect();
+        hdr[27] = [60., 60., 60.];
+
+        let plain = compute_dct(&hdr, 8, 8, 4, 4);
+        assert_eq!(compute_dct_with(&hdr, 8, 8, 4, 4, &EncodeOptions::default()).into_blurhash(), encode(&plain));
+
+        let options = EncodeOptions { tone_map: ToneMap::Reinhard, ..Default::default() };
+        let mapped: Vec<[f32; 3]> = hdr.iter().map(|&p| ToneMap::Reinhard.apply(p)).collect();
+        let dct = compute_dct_with(&hdr, 8, 8, 4, 4, &options);
+        assert!(plain.ac_max() > 1. && dct.ac_max() < 0.5, "{} {}", plain.ac_max(), dct.ac_max());
+        assert_eq!(dct.into_blurhash(), compute_dct(&mapped, 8, 8, 4, 4).into_blurhash());
+        assert_eq!(compute_dct_iter_with(hdr.iter(), 8, 8, 4, 4, &options).into_blurhash(),
+            compute_dct_iter(mapped.iter(), 8, 8, 4, 4).into_blurhash());
+    }
+
+    #[test]
+    fn test_compute_dct_composited() {
+        // a red square in the middle of a transparent (black) image
+        let image: Vec<[u8; 4]> = (0..64).map(|i| {
+            let (x, y) = (i % 8, i / 8);
+            if (2..6).contains(&x) && (2..6).contains(&y) { [255, 0, 0, 255] } else { [0, 0, 0, 0] }
+        }).collect();
+        let on_white: Vec<[u8; 3]> = image.iter().map(|p| if p[3] == 0 { [255; 3] } else { [p[0], p[1], p[2]] }).collect();
+        let expected = compute_dct(&on_white, 8, 8, 4, 3).into_blurhash();
+
+        let options = EncodeOptions { alpha: AlphaMode::Composite([1., 1., 1.]), ..Default::default() };
+        assert_eq!(compute_dct_with(&image, 8, 8, 4, 3, &options).into_blurhash(), expected);
+        let options = EncodeOptions { alpha: AlphaMode::ReplaceTransparent([1., 1., 1.]), ..Default::default() };
+        assert_eq!(compute_dct_iter_with(image.iter(), 8, 8, 4, 3, &options).into_blurhash(),
+            compute_dct_iter(on_white.iter(), 8, 8, 4, 3).into_blurhash());
+
+        assert_ne!(compute_dct(&image, 8, 8, 4, 3).into_blurhash(), expected);
+    }
+
+    #[test]
+    fn test_compute_dct_alpha_weighted() {
+        // an opaque red object on the left, garbage transparent pixels on the right
+        let image: Vec<[u8; 4]> = (0..64).map(|i| if i % 8 < 4 { [200, 30, 30, 255] } else { [0, 255, 0, 0] }).collect();
+        let options = EncodeOptions { alpha: AlphaMode::Weighted, ..Default::default() };
+
+        let dct = compute_dct_with(&image, 8, 8, 4, 3, &options);
+        assert_eq!(to_rgb(*dct.dc()), 0xC81E1E);
+        assert_eq!(compute_dct_iter_with(image.iter(), 8, 8, 4, 3, &options).dc(), dct.dc());
+
+        let opaque: Vec<[u8; 4]> = image.iter().map(|&[r, g, b, _]| [r, g, b, 255]).collect();
+        assert_eq!(compute_dct_with(&opaque, 8, 8, 4, 3, &options).into_blurhash(), compute_dct(&opaque, 8, 8, 4, 3).into_blurhash());
+
+        let transparent = [[255u8, 255, 255, 0]; 4];
+        let dct = compute_dct_with(&transparent, 2, 2, 3, 3, &options);
+        assert_eq!((*dct.dc(), dct.ac_max()), ([0.; 3], 0.));
+    }
+
+    #[test]
+    fn test_compute_dct_alpha_weighted_tone_mapped() {
+        // a translucent HDR object on the left, transparent highlights on the right
+        let image: Vec<[f32; 4]> = (0..64).map(|i| if i % 8 < 4 { [3., 1., 0., 0.5] } else { [9., 9., 9., 0.] }).collect();
+        let options = EncodeOptions { tone_map: ToneMap::Reinhard, alpha: AlphaMode::Weighted, ..Default::default() };
+
+        // the average color is the tone-mapped color of the object
+        let dct = compute_dct_with(&image, 8, 8, 4, 3, &options);
+        assert!(dct.dc().iter().zip([0.75, 0.5, 0.]).all(|(a, b)| (a - b).abs() < 1e-6), "{:?}", dct.dc());
+        assert_eq!(compute_dct_iter_with(image.iter(), 8, 8, 4, 3, &options).dc(), dct.dc());
+    }
+
+    #[test]
+    fn test_compute_dct_color_space() {
+        let image = test_pixels();
+        let srgb = compute_dct(&image, 8, 8, 4, 3).into_blurhash();
+
+        let p3 = EncodeOptions { color_space: ColorSpace::DisplayP3, ..Default::default() };
+        assert_ne!(compute_dct_with(&image, 8, 8, 4, 3, &p3).into_blurhash(), srgb);
+
+        // grays are the same in sRGB and Display P3
+        let gray: Vec<[u8; 3]> = image.iter().map(|p| [p[0]; 3]).collect();
+        assert_eq!(compute_dct_with(&gray, 8, 8, 4, 3, &p3).into_blurhash(), compute_dct(&gray, 8, 8, 4, 3).into_blurhash());
+    }
+
+    #[test]
+    fn test_render() {
+        let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let rgb8 = dct.to_image(8, 6, |col| col.map(linear_to_srgb));
+        assert_eq!(rgb8[0], [91, 67, 0]);
+
+        assert_eq!(dct.render::<[u8; 3]>(8, 6), rgb8);
+        assert_eq!(dct.render::<[u8; 4]>(8, 6), dct.to_image(8, 6, |col| {
+            let [r, g, b] = col.map(linear_to_srgb);
+            [r, g, b, 255]
+        }));
+        assert_eq!(dct.render::<u32>(8, 6), rgb8.iter().map(|&[r, g, b]| u32::from_be_bytes([255, r, g, b])).collect::<Vec<_>>());
+        assert_eq!(dct.render::<[f32; 3]>(8, 6), dct.to_image(8, 6, |col| col));
+
+        let rgb16 = dct.render::<[u16; 4]>(8, 6);
+        assert!(rgb16.iter().zip(&rgb8).all(|(a, b)| a[3] == 65535
+            && a.iter().zip(b).all(|(&a, &b)| (a as i32 - b as i32 * 257).abs() <= 128)));
+
+        // encoding back the rendered pixels gives the same blurhash
+        let luma = dct.render::<u8>(32, 24);
+        let gray: Vec<[u8; 3]> = luma.iter().map(|&l| [l; 3]).collect();
+        assert_eq!(compute_dct(&luma, 32, 24, 4, 3).into_blurhash(),
+            compute_dct(&gray, 32, 24, 4, 3).into_blurhash());
+        assert_eq!(luma[0], dct.render::<[u8; 2]>(32, 24)[0][0]);
+
+        let white = decode("00TSUA", 1.).unwrap();
+        assert_eq!(white.render::<u8>(1, 1), [255]);
+        assert_eq!(white.render::<u16>(1, 1), [65535]);
+        assert_eq!(white.render::<[f32; 4]>(1, 1), [[1., 1., 1., 1.]]);
+    }
+
+    #[test]
+    fn test_average_color() {
+        for blurhash in ["LlMF%n00%#MwS|WCWEM{R*bbWBbH", "KzKUZY=|HZ=|$5e9HZe9IS", "00TSUA", "U00000fQfQfQfQfQfQfQfQfQfQfQfQfQfQfQ"] {
+            let dc = decode(blurhash, 1.).unwrap().dc().map(linear_to_srgb);
+            assert_eq!(average_color(blurhash), Ok(dc), "{blurhash}");
+        }
+
+        assert_eq!(average_color(""), Err(BlurhashError::InvalidLength));
+        assert_eq!(average_color("LlMF%n00%#MwS|WCWEM{R*bbWBb"), Err(BlurhashError::InvalidLength));
+        assert_eq!(average_color("~0TSUA"), Err(BlurhashError::UnsupportedMode));
+        assert_eq!(average_color("°0TSUA"), Err(BlurhashError::BadFormat(base83::Base83ConversionError::InvalidChar)));
+        assert_eq!(average_color("0°SUA"), Err(BlurhashError::BadFormat(base83::Base83ConversionError::InvalidChar)));
+    }
+
+    #[cfg(feature = "ril")]
+    use ril::prelude::Image;
+
+    #[test]
+    #[cfg(feature = "ril")]
     fn test_encode_image() {
         let img = Image::<ril::pixel::Rgb>::open("test.webp").unwrap();
         let w = img.width() as usize;
@@ -645,6 +1094,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(feature = "ril")]
     fn test_decode_image() {
         let s = decode("vbHLxdSgNHxD~pX9R+i_NfNIt7V@NL%Mt7Rj-;t7e:WCj[WXV[ofM{WXbHof", 1.)
             .unwrap().to_rgb8(32, 48);
@@ -0,0 +1,156 @@
+//! Node.js native addon using `napi-rs` (requires the `node` feature)
+//!
+//! The shared library built with
+//! `cargo rustc --release --lib --crate-type cdylib --features node` can be
+//! loaded by Node once renamed to a `.node` addon. Both functions return a
+//! `Promise` and the computation runs on the libuv thread pool so the main
+//! thread is never blocked.
+//!
+//! #### Example
+//! ```js
+//! const { encode, decode } = require("./fast_blurhash.node");
+//!
+//! const { data, info } = await sharp("image.png").raw().toBuffer({ resolveWithObject: true });
+//! const blurhash = await encode(data, info.width, info.height, 4, 3);
+//! const pixels = await decode(blurhash, 32, 32); // RGBA Buffer
+//! ```
+
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::{Env, Error, Result, Task};
+use napi_derive::napi;
+use crate::compute_dct;
+
+/// Computes the blurhash of the pixels of a [`encode`] call
+pub struct EncodeTask {
+    pixels: Buffer,
+    width: usize,
+    height: usize,
+    x_components: usize,
+    y_components: usize,
+}
+
+impl Task for EncodeTask {
+    type Output = String;
+    type JsValue = String;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        if !(1..=9).contains(&self.x_components) || !(1..=9).contains(&self.y_components) {
+            return Err(Error::from_reason("The number of X and Y components must be between 1 and 9"))
+        }
+
+        let (width, height) = (self.width, self.height);
+        let dct = if self.pixels.len() == image_len(width, height, 3)? {
+            let pixels: Vec<[u8; 3]> = self.pixels.chunks_exact(3).map(|p| [p[0], p[1], p[2]]).collect();
+            compute_dct(&pixels, width, height, self.x_components, self.y_components)
+        } else if self.pixels.len() == image_len(width, height, 4)? {
+            let pixels: Vec<[u8; 4]> = self.pixels.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect();
+            compute_dct(&pixels, width, height, self.x_components, self.y_components)
+        } else {
+            return Err(Error::from_reason("The buffer must contain width * height RGB or RGBA pixels"))
+        };
+
+        Ok(dct.into_blurhash())
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+}
+
+/// Generates the pixels of a [`decode`] call
+pub struct DecodeTask {
+    blurhash: String,
+    width: usize,
+    height: usize,
+    punch: f32,
+}
+
+impl Task for DecodeTask {
+    type Output = Vec<u8>;
+    type JsValue = Buffer;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        image_len(self.width, self.height, 4)?;
+        let dct = crate::decode(&self.blurhash, self.punch).map_err(|err| Error::from_reason(err.to_string()))?;
+        Ok(dct.to_rgba8(self.width, self.height).into_iter().flatten().collect())
+    }
+
+    fn resolve(&mut self, _: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output.into())
+    }
+}
+
+/// Number of bytes of an image of (width * height) pixels, which can overflow
+/// on 32-bit targets with untrusted dimensions.
+fn image_len(width: usize, height: usize, channels: usize) -> Result<usize> {
+    width.checked_mul(height)
+        .and_then(|len| len.checked_mul(channels))
+        .ok_or_else(|| Error::from_reason("The image dimensions are too large"))
+}
+
+/// Compute the blurhash of an image of (width * height) RGB or RGBA pixels
+/// (alpha is ignored), the format is deduced from the length of the buffer.
+/// The number of X and Y components must be between 1 and 9.
+#[napi(ts_return_type = "Promise<string>")]
+pub fn encode(pixels: Buffer, width: u32, height: u32, x_components: u32, y_components: u32) -> AsyncTask<EncodeTask> {
+    AsyncTask::new(EncodeTask {
+        pixels,
+        width: width as usize,
+        height: height as usize,
+        x_components: x_components as usize,
+        y_components: y_components as usize,
+    })
+}
+
+/// Decode a blurhash into a buffer of (width * height) RGBA pixels (alpha will
+/// always be 255). The punch parameter defaults to 1.
+#[napi(ts_return_type = "Promise<Buffer>")]
+pub fn decode(blurhash: String, width: u32, height: u32, punch: Option<f64>) -> AsyncTask<DecodeTask> {
+    AsyncTask::new(DecodeTask {
+        blurhash,
+        width: width as usize,
+        height: height as usize,
+        punch: punch.unwrap_or(1.) as f32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_task(pixels: Vec<u8>, width: usize, height: usize) -> EncodeTask {
+        EncodeTask { pixels: pixels.into(), width, height, x_components: 3, y_components: 3 }
+    }
+
+    #[test]
+    fn test_encode_task() {
+        let image: [[u8; 3]; 4] = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+        let expected = compute_dct(&image, 2, 2, 3, 3).into_blurhash();
+
+        let mut rgb = encode_task(image.concat(), 2, 2);
+        assert_eq!(rgb.compute().unwrap(), expected);
+
+        let rgba = image.iter().flat_map(|&[r, g, b]| [r, g, b, 0]).collect();
+        assert_eq!(encode_task(rgba, 2, 2).compute().unwrap(), expected);
+
+        assert!(encode_task(vec![0; 10], 2, 2).compute().is_err());
+        rgb.x_components = 10;
+        assert!(rgb.compute().is_err());
+
+        let err = encode_task(vec![0; 12], usize::MAX / 2, 3).compute().unwrap_err();
+        assert_eq!(err.reason, "The image dimensions are too large");
+    }
+
+    #[test]
+    fn test_decode_task() {
+        let mut task = DecodeTask { blurhash: "LlMF%n00%#MwS|WCWEM{R*bbWBbH".to_string(), width: 4, height: 3, punch: 1. };
+        let expected = crate::decode(&task.blurhash, 1.).unwrap().to_rgba8(4, 3).concat();
+        assert_eq!(task.compute().unwrap(), expected);
+
+        task.blurhash = "LlMF%n00".to_string();
+        assert!(task.compute().is_err());
+
+        let mut task = DecodeTask { blurhash: "00TSUA".to_string(), width: usize::MAX / 4, height: 2, punch: 1. };
+        assert_eq!(task.compute().unwrap_err().reason, "The image dimensions are too large");
+    }
+}
@@ -0,0 +1,279 @@
+//! Options of the encoding process used by [`compute_dct_with`] and
+//! [`compute_dct_iter_with`]
+//!
+//! [`compute_dct_with`]: ../fn.compute_dct_with.html
+//! [`compute_dct_iter_with`]: ../fn.compute_dct_iter_with.html
+
+use crate::convert::{AsLinear, Linear};
+use crate::transfer::{AdobeRgb, Bt2020, Srgb, TransferFunction};
+
+/// Tone-mapping operator applied to every pixel in linear space before
+/// computing the DCT. High dynamic range images (such as floating-point
+/// buffers) can contain values above 1 which would otherwise dominate the
+/// maximum value of the alternative currents and flatten the other details.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ToneMap {
+    /// The colors are used as is (default)
+    #[default]
+    None,
+    /// Each channel is clamped between 0 and 1
+    Clamp,
+    /// Each channel is compressed with the Reinhard operator `x / (1 + x)`
+    /// which maps [0, ∞) to [0, 1) while keeping the details of the highlights
+    Reinhard,
+}
+
+impl ToneMap {
+    /// Apply the tone-mapping operator to a color in linear space.
+    #[inline]
+    pub fn apply(self, col: Linear) -> Linear {
+        match self {
+            ToneMap::None => col,
+            ToneMap::Clamp => col.map(|c| c.clamp(0., 1.)),
+            ToneMap::Reinhard => col.map(|c| {
+                let c = c.max(0.);
+                c / (1. + c)
+            }),
+        }
+    }
+}
+
+/// How the alpha channel of the pixels (see [`AsLinear::alpha`]) is handled
+/// before computing the DCT. A blurhash cannot store transparency, so without
+/// it transparent areas encode as whatever color they contain (often black).
+///
+/// [`AsLinear::alpha`]: ../convert/trait.AsLinear.html#method.alpha
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AlphaMode {
+    /// The alpha channel is ignored (default)
+    #[default]
+    Ignore,
+    /// The pixels are composited over a background color in linear space
+    Composite(Linear),
+    /// Fully transparent pixels are replaced by a color in linear space, the
+    /// other pixels are left untouched
+    ReplaceTransparent(Linear),
+    /// The contribution of each pixel is scaled by its alpha and the currents
+    /// are normalized by the sum of the alpha values instead of the number of
+    /// pixels, so only the visible parts of the image are represented (see
+    /// [`normalize_weighted_and_max`]).
+    ///
+    /// [`normalize_weighted_and_max`]: ../fn.normalize_weighted_and_max.html
+    Weighted,
+}
+
+impl AlphaMode {
+    /// Apply the alpha mode to a color in linear space with the given opacity
+    /// between 0 and 1.
+    #[inline]
+    pub fn apply(self, col: Linear, alpha: f32) -> Linear {
+        match self {
+            AlphaMode::Ignore => col,
+            AlphaMode::Composite(bg) => [
+                col[0] * alpha + bg[0] * (1. - alpha),
+                col[1] * alpha + bg[1] * (1. - alpha),
+                col[2] * alpha + bg[2] * (1. - alpha),
+            ],
+            AlphaMode::ReplaceTransparent(bg) if alpha <= 0. => bg,
+            AlphaMode::ReplaceTransparent(_) => col,
+            AlphaMode::Weighted => col.map(|c| c * alpha),
+        }
+    }
+}
+
+/// Color space of the input pixels. The pixels are converted to linear sRGB
+/// (the color space of the blurhash) with the transfer function and the 3x3
+/// matrix of the color space before computing the DCT.
+///
+/// Note: [`AsLinear`] decodes the channels with the sRGB transfer function,
+/// the conversion replaces it with the one of the color space. Pixels already
+/// in linear space (such as `[f32; 3]`) should be converted with
+/// [`ColorSpace::primaries_to_srgb`] beforehand instead.
+///
+/// [`AsLinear`]: ../convert/trait.AsLinear.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorSpace {
+    /// sRGB (default)
+    #[default]
+    Srgb,
+    /// Display P3: DCI-P3 primaries with a D65 white point and the sRGB
+    /// transfer function, used by Apple devices
+    DisplayP3,
+    /// Adobe RGB (1998) with a gamma of 563/256
+    AdobeRgb,
+    /// ITU-R BT.2020 with the BT.2020 (SDR) transfer function
+    Bt2020,
+}
+
+impl ColorSpace {
+    /// Convert a color with linear channels from the primaries of this color
+    /// space to the sRGB primaries. The result may be out of the sRGB gamut.
+    #[inline]
+    pub fn primaries_to_srgb(self, col: Linear) -> Linear {
+        let m = match self {
+            ColorSpace::Srgb => return col,
+            ColorSpace::DisplayP3 => [
+                [ 1.2249401, -0.2249404,  0.       ],
+                [-0.0420569,  1.0420571,  0.       ],
+                [-0.0196376, -0.0786361,  1.0982735],
+            ],
+            ColorSpace::AdobeRgb => [
+                [ 1.3982832, -0.3982831,  0.       ],
+                [ 0.,         1.,         0.       ],
+                [ 0.,        -0.0429383,  1.0429383],
+            ],
+            ColorSpace::Bt2020 => [
+                [ 1.660491,  -0.5876411, -0.0728499],
+                [-0.1245505,  1.1328999, -0.0083494],
+                [-0.0181508, -0.1005789,  1.1187297],
+            ],
+        };
+
+        [
+            m[0][0] * col[0] + m[0][1] * col[1] + m[0][2] * col[2],
+            m[1][0] * col[0] + m[1][1] * col[1] + m[1][2] * col[2],
+            m[2][0] * col[0] + m[2][1] * col[1] + m[2][2] * col[2],
+        ]
+    }
+
+    /// Convert a color decoded with the sRGB transfer function (as returned by
+    /// [`AsLinear::as_linear`]) to linear sRGB: the channels are decoded again
+    /// with the transfer function of this color space then converted to the
+    /// sRGB primaries.
+    ///
+    /// [`AsLinear::as_linear`]: ../convert/trait.AsLinear.html#tymethod.as_linear
+    #[inline]
+    pub fn to_linear_srgb(self, col: Linear) -> Linear {
+        let col = match self {
+            ColorSpace::Srgb | ColorSpace::DisplayP3 => col,
+            ColorSpace::AdobeRgb => col.map(|c| AdobeRgb.decode(Srgb.encode(c))),
+            ColorSpace::Bt2020 => col.map(|c| Bt2020.decode(Srgb.encode(c))),
+        };
+        self.primaries_to_srgb(col)
+    }
+}
+
+/// Options of the encoding process. The default options produce the same
+/// results as [`compute_dct`].
+///
+/// #### Example
+/// ```
+/// use fast_blurhash::{compute_dct_with, EncodeOptions, ToneMap};
+///
+/// let image: Vec<[f32; 3]> = vec![[4., 2., 1.]; 16 * 16];
+/// let options = EncodeOptions { tone_map: ToneMap::Reinhard, ..Default::default() };
+/// let blurhash = compute_dct_with(&image, 16, 16, 4, 3, &options).into_blurhash();
+/// ```
+///
+/// [`compute_dct`]: ../fn.compute_dct.html
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct EncodeOptions {
+    /// Tone-mapping operator applied to the colors, see [`ToneMap`]
+    pub tone_map: ToneMap,
+    /// Handling of the alpha channel, see [`AlphaMode`]
+    pub alpha: AlphaMode,
+    /// Color space of the input pixels, see [`ColorSpace`]
+    pub color_space: ColorSpace,
+}
+
+impl EncodeOptions {
+    /// Convert a pixel to linear sRGB: the color space conversion is applied
+    /// first, then the alpha mode and the tone-mapping operator. The weight of
+    /// [`AlphaMode::Weighted`] is applied last as the tone-mapping operators
+    /// do not commute with it.
+    #[inline]
+    pub(crate) fn linear_color<T: AsLinear>(&self, pixel: &T) -> Linear {
+        let col = self.color_space.to_linear_srgb(pixel.as_linear());
+        match self.alpha {
+            AlphaMode::Ignore => self.tone_map.apply(col),
+            AlphaMode::Weighted => AlphaMode::Weighted.apply(self.tone_map.apply(col), pixel.alpha()),
+            mode => self.tone_map.apply(mode.apply(col, pixel.alpha())),
+        }
+    }
+
+    /// Whether gray pixels stay gray once converted, that is unless they are
+    /// composited over or replaced by a colored background.
+    #[inline]
+    pub(crate) fn keeps_gray(&self) -> bool {
+        match self.alpha {
+            AlphaMode::Composite(bg) | AlphaMode::ReplaceTransparent(bg) => bg[0] == bg[1] && bg[1] == bg[2],
+            AlphaMode::Ignore | AlphaMode::Weighted => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tone_map() {
+        let col = [-0.5, 0.25, 3.];
+        assert_eq!(ToneMap::None.apply(col), col);
+        assert_eq!(ToneMap::Clamp.apply(col), [0., 0.25, 1.]);
+        assert_eq!(ToneMap::Reinhard.apply(col), [0., 0.2, 0.75]);
+    }
+
+    #[test]
+    fn test_alpha_mode() {
+        let col = [1., 0.5, 0.];
+        let bg = [0., 0., 1.];
+        assert_eq!(AlphaMode::Ignore.apply(col, 0.), col);
+        assert_eq!(AlphaMode::Composite(bg).apply(col, 1.), col);
+        assert_eq!(AlphaMode::Composite(bg).apply(col, 0.), bg);
+        assert_eq!(AlphaMode::Composite(bg).apply(col, 0.5), [0.5, 0.25, 0.5]);
+        assert_eq!(AlphaMode::ReplaceTransparent(bg).apply(col, 0.), bg);
+        assert_eq!(AlphaMode::ReplaceTransparent(bg).apply(col, 0.01), col);
+        assert_eq!(AlphaMode::Weighted.apply(col, 0.5), [0.5, 0.25, 0.]);
+    }
+
+    #[test]
+    fn test_to_linear() {
+        let options = EncodeOptions { tone_map: ToneMap::Clamp, alpha: AlphaMode::Composite([4., 4., 4.]), ..Default::default() };
+        assert_eq!(options.linear_color(&[255u8, 255, 255, 255]), [1., 1., 1.]);
+        assert_eq!(options.linear_color(&[0u8, 0, 0, 0]), [1., 1., 1.]);
+        assert_eq!(options.linear_color(&0x00FFFFFFu32), [1., 1., 1.]);
+
+        // the weight is applied after tone-mapping
+        let options = EncodeOptions { tone_map: ToneMap::Reinhard, alpha: AlphaMode::Weighted, ..Default::default() };
+        assert_eq!(options.linear_color(&[3f32, 1., 0., 0.5]), [0.375, 0.25, 0.]);
+    }
+
+    fn assert_close(a: Linear, b: Linear) {
+        assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 2e-3), "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn test_color_space() {
+        let white = [1., 1., 1.];
+        for space in [ColorSpace::Srgb, ColorSpace::DisplayP3, ColorSpace::AdobeRgb, ColorSpace::Bt2020] {
+            assert_close(space.to_linear_srgb(white), white);
+            assert_close(space.to_linear_srgb([0.; 3]), [0.; 3]);
+        }
+
+        // pure P3 red is out of the sRGB gamut
+        let red = ColorSpace::DisplayP3.to_linear_srgb([1., 0., 0.]);
+        assert!(red[0] > 1. && red[1] < 0.);
+
+        // mid gray keeps the same luminance with the sRGB transfer function
+        let gray = crate::convert::srgb_to_linear(128);
+        assert_close(ColorSpace::DisplayP3.to_linear_srgb([gray; 3]), [gray; 3]);
+        // but not with the Adobe RGB one (0.502^2.2 ≈ 0.2195)
+        assert_close(ColorSpace::AdobeRgb.to_linear_srgb([gray; 3]), [0.2195; 3]);
+    }
+
+    #[test]
+    fn test_color_space_hdr() {
+        // values out of [0, 1] are converted without being clipped
+        for space in [ColorSpace::DisplayP3, ColorSpace::AdobeRgb, ColorSpace::Bt2020] {
+            let hdr = space.to_linear_srgb([4., 2., -0.01]);
+            assert!(hdr.iter().all(|c| c.is_finite()), "{space:?} {hdr:?}");
+            assert!(hdr[0] > 2. && hdr[1] > 1., "{space:?} {hdr:?}");
+            assert!(hdr[2] < 0., "{space:?} {hdr:?}");
+
+            let options = EncodeOptions { tone_map: ToneMap::Reinhard, color_space: space, ..Default::default() };
+            let mapped = options.linear_color(&[4f32, 4., 4.]);
+            assert!(mapped.iter().all(|&c| c > 0.7 && c < 1.), "{space:?} {mapped:?}");
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Pixels packed in an `u32` with an explicit channel order, byte order and
+//! alpha premultiplication
+//!
+//! `AsLinear` for `u32` and [`DCTResult::to_rgba`] use the `0xAARRGGBB` layout.
+//! Buffers coming from graphics libraries often use another one which can be
+//! described with a [`PackedFormat`].
+//!
+//! #### Example
+//! ```
+//! use fast_blurhash::{compute_dct_iter, decode};
+//! use fast_blurhash::packed::{Packed, PackedFormat};
+//!
+//! let (width, height) = (4, 4);
+//! let image: Vec<u32> = vec![0xFF336699; width * height]; // from cairo
+//! let pixels = image.iter().map(|&p| Packed(p, PackedFormat::CAIRO_ARGB32));
+//! let blurhash = compute_dct_iter(pixels, width, height, 4, 3).into_blurhash();
+//!
+//! let placeholder: Vec<u32> = decode(&blurhash, 1.).unwrap().to_packed(32, 32, PackedFormat::WINDOWS_BGRA);
+//! ```
+//!
+//! [`DCTResult::to_rgba`]: ../struct.DCTResult.html#method.to_rgba
+
+use crate::DCTResult;
+use crate::convert::*;
+
+/// Order of the channels in memory once the `u32` is stored with the byte
+/// order of the format
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChannelOrder {
+    /// [Red, Green, Blue, Alpha]
+    Rgba,
+    /// [Blue, Green, Red, Alpha]
+    Bgra,
+    /// [Alpha, Red, Green, Blue]
+    Argb,
+    /// [Alpha, Blue, Green, Red]
+    Abgr,
+}
+
+/// Byte order used to store the `u32` in memory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Endian {
+    /// The most significant byte comes first, the channel order is the order
+    /// of the channels in the hexadecimal notation (`0xAARRGGBB` is `Argb`).
+    Big,
+    /// The least significant byte comes first (`0xAARRGGBB` is `Bgra`).
+    Little,
+    /// Byte order of the target, for `u32` read directly from a byte buffer
+    /// (the channel order is then the order of the bytes in the buffer).
+    Native,
+}
+
+/// Layout of a pixel packed in an `u32`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PackedFormat {
+    /// Order of the channels in memory
+    pub order: ChannelOrder,
+    /// Byte order used to store the `u32`
+    pub endian: Endian,
+    /// Whether the color channels are premultiplied by the alpha channel
+    pub premultiplied: bool,
+}
+
+impl PackedFormat {
+    /// `0xAARRGGBB` layout used by `AsLinear` for `u32` and `DCTResult::to_rgba`
+    pub const ARGB: PackedFormat = PackedFormat { order: ChannelOrder::Argb, endian: Endian::Big, premultiplied: false };
+    /// Cairo's `CAIRO_FORMAT_ARGB32`: premultiplied `0xAARRGGBB` stored in
+    /// native byte order
+    pub const CAIRO_ARGB32: PackedFormat = PackedFormat { order: ChannelOrder::Argb, endian: Endian::Big, premultiplied: true };
+    /// Skia's `kRGBA_8888_SkColorType` with premultiplied alpha: R, G, B, A bytes
+    pub const SKIA_RGBA: PackedFormat = PackedFormat { order: ChannelOrder::Rgba, endian: Endian::Native, premultiplied: true };
+    /// Skia's `kBGRA_8888_SkColorType` with premultiplied alpha: B, G, R, A bytes
+    pub const SKIA_BGRA: PackedFormat = PackedFormat { order: ChannelOrder::Bgra, endian: Endian::Native, premultiplied: true };
+    /// Windows GDI/Direct2D 32-bit bitmaps: B, G, R, A bytes
+    pub const WINDOWS_BGRA: PackedFormat = PackedFormat { order: ChannelOrder::Bgra, endian: Endian::Native, premultiplied: false };
+    /// R, G, B, A bytes such as the canvas `ImageData`
+    pub const RGBA_BYTES: PackedFormat = PackedFormat { order: ChannelOrder::Rgba, endian: Endian::Native, premultiplied: false };
+
+    /// Unpack a pixel as [RR, GG, BB, AA] with straight (non-premultiplied)
+    /// alpha.
+    pub fn unpack(self, pixel: u32) -> [u8; 4] {
+        let bytes = match self.endian {
+            Endian::Big => pixel.to_be_bytes(),
+            Endian::Little => pixel.to_le_bytes(),
+            Endian::Native => pixel.to_ne_bytes(),
+        };
+
+        let [r, g, b, a] = match self.order {
+            ChannelOrder::Rgba => bytes,
+            ChannelOrder::Bgra => [bytes[2], bytes[1], bytes[0], bytes[3]],
+            ChannelOrder::Argb => [bytes[1], bytes[2], bytes[3], bytes[0]],
+            ChannelOrder::Abgr => [bytes[3], bytes[2], bytes[1], bytes[0]],
+        };
+
+        if self.premultiplied && a < 255 {
+            let unpremultiply = |c: u8| if a == 0 { 0 } else { ((c as u32 * 255 + a as u32 / 2) / a as u32).min(255) as u8 };
+            [unpremultiply(r), unpremultiply(g), unpremultiply(b), a]
+        } else {
+            [r, g, b, a]
+        }
+    }
+
+    /// Pack a pixel given as [RR, GG, BB, AA] with straight (non-premultiplied)
+    /// alpha.
+    pub fn pack(self, [r, g, b, a]: [u8; 4]) -> u32 {
+        let [r, g, b] = if self.premultiplied && a < 255 {
+            [r, g, b].map(|c| ((c as u32 * a as u32 + 127) / 255) as u8)
+        } else {
+            [r, g, b]
+        };
+
+        let bytes = match self.order {
+            ChannelOrder::Rgba => [r, g, b, a],
+            ChannelOrder::Bgra => [b, g, r, a],
+            ChannelOrder::Argb => [a, r, g, b],
+            ChannelOrder::Abgr => [a, b, g, r],
+        };
+
+        match self.endian {
+            Endian::Big => u32::from_be_bytes(bytes),
+            Endian::Little => u32::from_le_bytes(bytes),
+            Endian::Native => u32::from_ne_bytes(bytes),
+        }
+    }
+}
+
+/// A pixel packed in an `u32` with the given [`PackedFormat`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Packed(pub u32, pub PackedFormat);
+
+impl AsLinear for Packed {
+    fn as_linear(&self) -> Linear {
+        self.1.unpack(self.0).as_linear()
+    }
+
+    fn alpha(&self) -> f32 {
+        self.1.unpack(self.0).alpha()
+    }
+}
+
+impl AsLinear for &Packed {
+    fn as_linear(&self) -> Linear {
+        self.1.unpack(self.0).as_linear()
+    }
+
+    fn alpha(&self) -> f32 {
+        self.1.unpack(self.0).alpha()
+    }
+}
+
+impl DCTResult {
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image. This function allocates a vector of (width * height) u32 in
+    /// the sRGB space packed with the given format (alpha will always be 255).
+    pub fn to_packed(&self, width: usize, height: usize, format: PackedFormat) -> Vec<u32> {
+        self.to_rgba8(width, height).into_iter().map(|pixel| format.pack(pixel)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_dct, compute_dct_iter, decode};
+    use crate::tests::test_pixels;
+
+    #[test]
+    fn test_unpack() {
+        assert_eq!(PackedFormat::ARGB.unpack(0x80112233), [0x11, 0x22, 0x33, 0x80]);
+        let abgr = PackedFormat { order: ChannelOrder::Abgr, endian: Endian::Little, premultiplied: false };
+        assert_eq!(abgr.unpack(0x11223380), [0x11, 0x22, 0x33, 0x80]);
+        assert_eq!(PackedFormat::RGBA_BYTES.unpack(u32::from_ne_bytes([1, 2, 3, 4])), [1, 2, 3, 4]);
+        assert_eq!(PackedFormat::WINDOWS_BGRA.unpack(u32::from_ne_bytes([1, 2, 3, 4])), [3, 2, 1, 4]);
+
+        // premultiplied
+        assert_eq!(PackedFormat::CAIRO_ARGB32.unpack(0x80400000), [0x80, 0, 0, 0x80]);
+        assert_eq!(PackedFormat::CAIRO_ARGB32.unpack(0x00000000), [0, 0, 0, 0]);
+        assert_eq!(PackedFormat::SKIA_RGBA.unpack(u32::from_ne_bytes([255, 0, 0, 255])), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_pack_roundtrip() {
+        let formats = [PackedFormat::ARGB, PackedFormat::CAIRO_ARGB32, PackedFormat::SKIA_RGBA,
+            PackedFormat::SKIA_BGRA, PackedFormat::WINDOWS_BGRA, PackedFormat::RGBA_BYTES];
+        for format in formats {
+            for pixel in [[0x11, 0x22, 0x33, 0xFF], [0xFF, 0x80, 0x00, 0xFF], [0x00, 0x00, 0x00, 0x00]] {
+                assert_eq!(format.unpack(format.pack(pixel)), pixel, "{format:?}");
+            }
+        }
+        assert_eq!(PackedFormat::ARGB.pack([0x11, 0x22, 0x33, 0x44]), 0x44112233);
+        assert_eq!(PackedFormat::CAIRO_ARGB32.pack([0xFF, 0x00, 0x00, 0x80]), 0x80800000);
+    }
+
+    #[test]
+    fn test_encode_packed() {
+        let image: Vec<u32> = test_pixels().iter().map(|&[r, g, b]| u32::from_be_bytes([0xFF, r, g, b])).collect();
+        let expected = compute_dct(&image, 8, 8, 4, 3).into_blurhash();
+
+        for format in [PackedFormat::CAIRO_ARGB32, PackedFormat::SKIA_RGBA, PackedFormat::WINDOWS_BGRA] {
+            let packed: Vec<Packed> = image.iter().map(|&p| Packed(format.pack(PackedFormat::ARGB.unpack(p)), format)).collect();
+            assert_eq!(compute_dct(&packed, 8, 8, 4, 3).into_blurhash(), expected);
+            assert_eq!(compute_dct_iter(packed.iter(), 8, 8, 4, 3).into_blurhash(),
+                compute_dct_iter(image.iter().copied(), 8, 8, 4, 3).into_blurhash());
+        }
+    }
+
+    #[test]
+    fn test_to_packed() {
+        let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        assert_eq!(dct.to_packed(8, 6, PackedFormat::ARGB), dct.to_rgba(8, 6));
+
+        let bgra = dct.to_packed(8, 6, PackedFormat::WINDOWS_BGRA);
+        let expected: Vec<[u8; 4]> = dct.to_rgba8(8, 6).into_iter().map(|[r, g, b, a]| [b, g, r, a]).collect();
+        assert_eq!(bgra.into_iter().map(u32::to_ne_bytes).collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,239 @@
+//! Minimal dependency-free PNG encoder and data URI utilities
+//!
+//! The generated PNGs are not compressed (the zlib stream only contains
+//! stored blocks) which is fine for the small size of placeholders.
+
+use crate::DCTResult;
+use crate::alpha::AlphaDCTResult;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+
+/// Maximum length of a stored deflate block
+const MAX_BLOCK_LEN: usize = 65535;
+
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut n = 0;
+    while n < 256 {
+        let mut c = n as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 { 0xEDB88320 ^ (c >> 1) } else { c >> 1 };
+            k += 1;
+        }
+        table[n] = c;
+        n += 1;
+    }
+    table
+}
+
+/// Computes the CRC-32 of the chunk type and data as specified by the PNG format.
+pub fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(!0u32, |c, &b| CRC_TABLE[((c ^ b as u32) & 0xFF) as usize] ^ (c >> 8))
+}
+
+/// Computes the Adler-32 checksum of the uncompressed zlib data.
+pub fn adler32(bytes: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in bytes.chunks(5552) { // largest n such that no overflow can occur
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+/// Encodes an image of 8-bit RGB pixels into a PNG file. The slice must be long
+/// enough (it must have at least width * height items) and the image can't be
+/// empty.
+pub fn encode_rgb8(pixels: &[[u8; 3]], width: usize, height: usize) -> Vec<u8> {
+    assert!(pixels.len() >= width * height);
+    encode(pixels[..width * height].iter().flatten().copied(), width, height, 3)
+}
+
+/// Encodes an image of 8-bit RGBA pixels (alpha is not premultiplied) into a
+/// PNG file. The slice must be long enough (it must have at least width * height
+/// items) and the image can't be empty.
+pub fn encode_rgba8(pixels: &[[u8; 4]], width: usize, height: usize) -> Vec<u8> {
+    assert!(pixels.len() >= width * height);
+    encode(pixels[..width * height].iter().flatten().copied(), width, height, 4)
+}
+
+/// Converts bytes into a `data:` URI using the base64 encoding.
+pub fn data_uri(mime: &str, bytes: &[u8]) -> String {
+    const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut uri = String::with_capacity(5 + mime.len() + 8 + bytes.len().div_ceil(3) * 4);
+    uri.push_str("data:");
+    uri.push_str(mime);
+    uri.push_str(";base64,");
+
+    for chunk in bytes.chunks(3) {
+        let n = (chunk[0] as u32) << 16
+            | (*chunk.get(1).unwrap_or(&0) as u32) << 8
+            | *chunk.get(2).unwrap_or(&0) as u32;
+
+        uri.push(BASE64[(n >> 18) as usize & 63] as char);
+        uri.push(BASE64[(n >> 12) as usize & 63] as char);
+        uri.push(if chunk.len() > 1 { BASE64[(n >> 6) as usize & 63] as char } else { '=' });
+        uri.push(if chunk.len() > 2 { BASE64[n as usize & 63] as char } else { '=' });
+    }
+
+    uri
+}
+
+/// Encodes the interleaved 8-bit samples of an image with `channels` channels
+/// (3 for RGB and 4 for RGBA).
+fn encode(samples: impl Iterator<Item = u8>, width: usize, height: usize, channels: usize) -> Vec<u8> {
+    // the dimensions are non-zero 31-bit integers
+    assert!((1..=i32::MAX as usize).contains(&width) && (1..=i32::MAX as usize).contains(&height),
+        "Invalid PNG dimensions {width}x{height}, they must be between 1 and 2^31 - 1");
+    let color_type = if channels == 4 { 6 } else { 2 };
+    let stride = width * channels;
+
+    // every scanline starts with its filter type (0 = None)
+    let mut raw = Vec::with_capacity((stride + 1) * height);
+    let mut samples = samples;
+    for _ in 0..height {
+        raw.push(0);
+        raw.extend(samples.by_ref().take(stride));
+    }
+
+    let blocks = raw.len().div_ceil(MAX_BLOCK_LEN).max(1);
+    let mut zlib = Vec::with_capacity(2 + raw.len() + blocks * 5 + 4);
+    zlib.extend_from_slice(&[0x78, 0x01]); // deflate, 32K window, no preset dictionary
+    for i in 0..blocks {
+        let block = &raw[(i * MAX_BLOCK_LEN)..raw.len().min((i + 1) * MAX_BLOCK_LEN)];
+        let len = block.len() as u16;
+        zlib.push((i + 1 == blocks) as u8); // BFINAL bit and stored block type
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+    zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+
+    let mut header = [0u8; 13];
+    header[0..4].copy_from_slice(&(width as u32).to_be_bytes());
+    header[4..8].copy_from_slice(&(height as u32).to_be_bytes());
+    header[8] = 8; // bit depth
+    header[9] = color_type;
+    // compression, filter and interlace methods are all 0
+
+    let mut png = Vec::with_capacity(SIGNATURE.len() + 3 * 12 + header.len() + zlib.len());
+    png.extend_from_slice(&SIGNATURE);
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &zlib);
+    write_chunk(&mut png, b"IEND", &[]);
+    png
+}
+
+/// Appends a chunk (length, type, data and CRC) to the PNG file.
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = png.len();
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let crc = crc32(&png[start..]);
+    png.extend_from_slice(&crc.to_be_bytes());
+}
+
+impl DCTResult {
+    /// Generate a PNG file of (width * height) pixels from this DCT Result. The
+    /// width and the height can't be 0.
+    pub fn to_png(&self, width: usize, height: usize) -> Vec<u8> {
+        encode_rgb8(&self.to_rgb8(width, height), width, height)
+    }
+
+    /// Generate a PNG file of (width * height) pixels from this DCT Result and
+    /// convert it into a `data:image/png;base64,...` URI which can be embedded
+    /// in HTML or JSON.
+    pub fn to_data_uri(&self, width: usize, height: usize) -> String {
+        data_uri("image/png", &self.to_png(width, height))
+    }
+}
+
+impl AlphaDCTResult {
+    /// Generate a transparent PNG file of (width * height) pixels from this DCT
+    /// Result.
+    pub fn to_png(&self, width: usize, height: usize) -> Vec<u8> {
+        encode_rgba8(&self.to_rgba8(width, height), width, height)
+    }
+
+    /// Generate a transparent PNG file of (width * height) pixels from this DCT
+    /// Result and convert it into a `data:image/png;base64,...` URI.
+    pub fn to_data_uri(&self, width: usize, height: usize) -> String {
+        data_uri("image/png", &self.to_png(width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ril::prelude::{Image, ImageFormat};
+
+    #[test]
+    fn test_checksums() {
+        assert_eq!(crc32(b"IEND"), 0xAE426082);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+    }
+
+    #[test]
+    fn test_data_uri() {
+        assert_eq!(data_uri("text/plain", b"Man"), "data:text/plain;base64,TWFu");
+        assert_eq!(data_uri("text/plain", b"Ma"), "data:text/plain;base64,TWE=");
+        assert_eq!(data_uri("text/plain", b"M"), "data:text/plain;base64,TQ==");
+        assert_eq!(data_uri("text/plain", b""), "data:text/plain;base64,");
+    }
+
+    #[test]
+    fn test_encode_rgb8() {
+        let pixels: Vec<[u8; 3]> = (0..12).map(|i| [i * 20, 255 - i * 20, i]).collect();
+        let png = encode_rgb8(&pixels, 4, 3);
+
+        let img = Image::<ril::pixel::Rgb>::from_bytes(ImageFormat::Png, &png).unwrap();
+        assert_eq!((img.width(), img.height()), (4, 3));
+        for (i, p) in img.pixels().flatten().enumerate() {
+            assert_eq!([p.r, p.g, p.b], pixels[i]);
+        }
+    }
+
+    #[test]
+    fn test_dct_to_png() {
+        let dct = crate::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let png = dct.to_png(32, 24);
+
+        let img = Image::<ril::pixel::Rgb>::from_bytes(ImageFormat::Png, &png).unwrap();
+        let expected = dct.to_rgb8(32, 24);
+        for (i, p) in img.pixels().flatten().enumerate() {
+            assert_eq!([p.r, p.g, p.b], expected[i]);
+        }
+
+        assert_eq!(dct.to_data_uri(32, 24), data_uri("image/png", &png));
+        assert!(dct.to_data_uri(32, 24).starts_with("data:image/png;base64,iVBORw0KGgo"));
+    }
+
+    #[test]
+    fn test_encode_rgba8_multiple_blocks() {
+        // 200 * 100 * 4 bytes do not fit in a single stored block
+        let pixels: Vec<[u8; 4]> = (0..200 * 100).map(|i| [i as u8, (i >> 8) as u8, 7, (i % 255) as u8]).collect();
+        let png = encode_rgba8(&pixels, 200, 100);
+
+        let img = Image::<ril::pixel::Rgba>::from_bytes(ImageFormat::Png, &png).unwrap();
+        assert_eq!((img.width(), img.height()), (200, 100));
+        for (i, p) in img.pixels().flatten().enumerate() {
+            assert_eq!([p.r, p.g, p.b, p.a], pixels[i]);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid PNG dimensions 0x24")]
+    fn test_encode_empty() {
+        crate::decode("00TSUA", 1.).unwrap().to_png(0, 24);
+    }
+}
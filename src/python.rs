@@ -0,0 +1,125 @@
+//! Python bindings using `pyo3` (requires the `python` feature)
+//!
+//! The extension module is built with [maturin](https://www.maturin.rs)
+//! (`maturin develop --release`) and exposes two functions working with numpy
+//! arrays of shape (height, width, channels) and dtype `uint8`.
+//!
+//! #### Example
+//! ```python
+//! import fast_blurhash
+//! import numpy as np
+//! from PIL import Image
+//!
+//! image = np.asarray(Image.open("image.png").convert("RGB"))
+//! blurhash = fast_blurhash.encode(image, 4, 3)
+//! pixels = fast_blurhash.decode(blurhash, 32, 32, 1.0)  # shape (32, 32, 3)
+//! ```
+
+use numpy::{PyArray1, PyArray3, PyArrayMethods};
+use pyo3::buffer::PyBuffer;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use crate::{BlurhashError, compute_dct};
+
+/// Compute the blurhash of an image stored in a C-contiguous uint8 array of
+/// shape (height, width, 3) for RGB or (height, width, 4) for RGBA (alpha is
+/// ignored). The pixels are read in place through the buffer protocol so any
+/// object supporting it (numpy arrays, memoryviews...) can be used.
+#[pyfunction]
+#[pyo3(signature = (image, x_components=4, y_components=3))]
+fn encode(image: PyBuffer<u8>, x_components: usize, y_components: usize) -> PyResult<String> {
+    let &[height, width, channels] = image.shape() else {
+        return Err(PyValueError::new_err("The image must be an array of shape (height, width, channels)"))
+    };
+
+    if !(3..=4).contains(&channels) {
+        return Err(PyValueError::new_err("The image must have 3 (RGB) or 4 (RGBA) channels"))
+    }
+
+    if !image.is_c_contiguous() {
+        return Err(PyValueError::new_err("The image must be C-contiguous"))
+    }
+
+    if !(1..=9).contains(&x_components) || !(1..=9).contains(&y_components) {
+        return Err(PyValueError::new_err("The number of X and Y components must be between 1 and 9"))
+    }
+
+    // SAFETY: the buffer is C-contiguous, holds height * width pixels of
+    // `channels` bytes (which have the same layout as byte arrays) and stays
+    // alive (and the GIL held) for the duration of the computation.
+    let dct = if channels == 3 {
+        let pixels = unsafe { std::slice::from_raw_parts(image.buf_ptr() as *const [u8; 3], height * width) };
+        compute_dct(pixels, width, height, x_components, y_components)
+    } else {
+        let pixels = unsafe { std::slice::from_raw_parts(image.buf_ptr() as *const [u8; 4], height * width) };
+        compute_dct(pixels, width, height, x_components, y_components)
+    };
+
+    Ok(dct.into_blurhash())
+}
+
+/// Decode a blurhash into a numpy uint8 array of shape (height, width, 3)
+/// containing the RGB pixels of the generated image.
+#[pyfunction]
+#[pyo3(signature = (blurhash, width, height, punch=1.))]
+fn decode<'py>(py: Python<'py>, blurhash: &str, width: usize, height: usize, punch: f32) -> PyResult<Bound<'py, PyArray3<u8>>> {
+    let dct = crate::decode(blurhash, punch).map_err(to_py_err)?;
+    let pixels: Vec<u8> = dct.to_rgb8(width, height).into_iter().flatten().collect();
+    PyArray1::from_vec(py, pixels).reshape([height, width, 3])
+}
+
+fn to_py_err(err: BlurhashError) -> PyErr {
+    PyValueError::new_err(err.to_string())
+}
+
+/// The `fast_blurhash` Python module
+#[pymodule]
+fn fast_blurhash(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(encode, m)?)?;
+    m.add_function(wrap_pyfunction!(decode, m)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pyo3::types::PyDict;
+
+    /// Run a test with an embedded interpreter, the `auto-initialize` feature
+    /// of pyo3 is not enabled to only link against Python with this feature.
+    fn with_python<R>(f: impl for<'py> FnOnce(Python<'py>) -> R) -> R {
+        Python::initialize();
+        Python::attach(f)
+    }
+
+    fn call_encode(py: Python<'_>, code: &std::ffi::CStr) -> PyResult<String> {
+        let locals = PyDict::new(py);
+        let image = py.eval(code, None, Some(&locals))?;
+        encode(image.extract()?, 3, 3)
+    }
+
+    #[test]
+    fn test_encode_buffer() {
+        with_python(|py| {
+            let image: [[u8; 3]; 4] = [[255, 0, 0], [0, 255, 0], [0, 0, 255], [255, 255, 255]];
+            let expected = compute_dct(&image, 2, 2, 3, 3).into_blurhash();
+
+            let rgb = call_encode(py, c"memoryview(bytes([255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255])).cast('B', (2, 2, 3))");
+            assert_eq!(rgb.unwrap(), expected);
+
+            let rgba = call_encode(py, c"memoryview(bytes([255, 0, 0, 9, 0, 255, 0, 9, 0, 0, 255, 9, 255, 255, 255, 9])).cast('B', (2, 2, 4))");
+            assert_eq!(rgba.unwrap(), expected);
+        });
+    }
+
+    #[test]
+    fn test_invalid_arguments() {
+        with_python(|py| {
+            assert!(call_encode(py, c"memoryview(bytes(12))").is_err());
+            assert!(call_encode(py, c"memoryview(bytes(8)).cast('B', (2, 2, 2))").is_err());
+
+            let err = decode(py, "LlMF%n00", 32, 32, 1.).unwrap_err();
+            assert!(err.is_instance_of::<PyValueError>(py));
+        });
+    }
+}
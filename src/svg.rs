@@ -0,0 +1,98 @@
+//! SVG output to inline resolution-independent placeholders
+
+use std::fmt::Write;
+use crate::DCTResult;
+use crate::css::hex;
+
+impl DCTResult {
+    /// Generate a SVG placeholder of (width * height) user units from this DCT
+    /// Result. The image is sampled on a grid of rectangles (two per X and Y
+    /// component) which are smoothed by a gaussian blur filter. The SVG is
+    /// stretched to fill its box so it can be scaled to any size. The id of the
+    /// filter is derived from the components so several placeholders can be
+    /// inlined in the same document.
+    pub fn to_svg(&self, width: usize, height: usize) -> String {
+        let (cols, rows) = (self.x_components * 2, self.y_components * 2);
+        let cells = self.sample_cells(cols, rows);
+        let id = filter_id(self);
+
+        let mut svg = String::with_capacity(384 + cells.len() * 56);
+        let _ = write!(svg, "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+            viewBox=\"0 0 {cols} {rows}\" preserveAspectRatio=\"none\">");
+
+        // the blur fades the edges into transparency, the alpha is restored
+        // to keep the edges of the placeholder sharp
+        let _ = write!(svg, "<filter id=\"{id}\" x=\"0\" y=\"0\" width=\"100%\" height=\"100%\" \
+            color-interpolation-filters=\"sRGB\"><feGaussianBlur stdDeviation=\"0.5\" edgeMode=\"duplicate\"/>\
+            <feComponentTransfer><feFuncA type=\"discrete\" tableValues=\"1 1\"/></feComponentTransfer></filter>");
+
+        let _ = write!(svg, "<g filter=\"url(#{id})\">");
+        for (i, &color) in cells.iter().enumerate() {
+            let _ = write!(svg, "<rect x=\"{}\" y=\"{}\" width=\"1\" height=\"1\" fill=\"{}\"/>",
+                i % cols, i / cols, hex(color));
+        }
+        svg.push_str("</g></svg>");
+
+        svg
+    }
+}
+
+/// Id of the SVG filter of a DCT Result: the number of components and the bits
+/// of the currents are hashed with FNV-1a. The blurhash itself can't be used as
+/// its base83 characters are not all valid in a fragment identifier, and it
+/// only exists for up to 9 components.
+fn filter_id(dct: &DCTResult) -> String {
+    let words = [dct.x_components as u32, dct.y_components as u32].into_iter()
+        .chain(dct.currents.iter().flatten().map(|f| f.to_bits()));
+    let hash = words.flat_map(u32::to_le_bytes).fold(0x811c9dc5u32, |hash, b| (hash ^ b as u32).wrapping_mul(0x01000193));
+    format!("blurhash-{hash:08x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{compute_dct, decode};
+
+    #[test]
+    fn test_svg_uniform() {
+        let dct = decode("00TSUA", 1.).unwrap();
+        let svg = dct.to_svg(32, 24);
+
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"32\" height=\"24\" viewBox=\"0 0 2 2\""));
+        assert!(svg.ends_with("</g></svg>"));
+        assert_eq!(svg.matches("<rect ").count(), 4);
+        assert_eq!(svg.matches("fill=\"#ffffff\"").count(), 4);
+    }
+
+    #[test]
+    fn test_svg_grid() {
+        let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let svg = dct.to_svg(100, 100);
+
+        let cells = dct.sample_cells(8, 6);
+        assert_eq!(svg.matches("<rect ").count(), cells.len());
+        assert!(svg.contains(&format!("<rect x=\"7\" y=\"5\" width=\"1\" height=\"1\" fill=\"#{:06x}\"/>", cells[47])));
+    }
+
+    #[test]
+    fn test_svg_filter_id() {
+        let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        let a = dct.to_svg(32, 24);
+        let b = decode("00TSUA", 1.).unwrap().to_svg(32, 24);
+
+        let id = super::filter_id(&dct);
+        assert!(id.starts_with("blurhash-") && id[9..].chars().all(|c| c.is_ascii_hexdigit()));
+        assert!(a.contains(&format!("<filter id=\"{id}\"")));
+        assert!(a.contains(&format!("filter=\"url(#{id})\"")));
+        assert!(!b.contains(&id));
+    }
+
+    #[test]
+    fn test_svg_many_components() {
+        let image: Vec<[u8; 3]> = (0..400u32).map(|i| [(i % 256) as u8, (i / 2) as u8, 128]).collect();
+        let dct = compute_dct(&image, 20, 20, 10, 10);
+        let svg = dct.to_svg(20, 20);
+
+        assert_eq!(svg.matches("<rect ").count(), 400);
+        assert!(svg.contains(&format!("<filter id=\"{}\"", super::filter_id(&dct))));
+    }
+}
@@ -0,0 +1,396 @@
+//! Transfer functions converting encoded channel values to the linear space
+//! and back
+//!
+//! The built-in pixel types are decoded with [`Srgb`] (see
+//! [`srgb_to_linear`] and [`linear_to_srgb`]), and the color spaces of
+//! [`EncodeOptions`] with [`AdobeRgb`] and [`Bt2020`]. Pixels encoded with
+//! another transfer function, such as [`Pq`] or [`Hlg`] for HDR content, can be
+//! wrapped in [`WithTransfer`] to be encoded, and [`DCTResult::to_rgb8_with`]
+//! renders an image with any transfer function.
+//!
+//! #### Example
+//! ```
+//! use fast_blurhash::{compute_dct_iter, decode};
+//! use fast_blurhash::transfer::{Gamma22, WithTransfer};
+//!
+//! let image: Vec<[u8; 3]> = vec![[200, 120, 40]; 16 * 16];
+//! let pixels = image.iter().map(|&p| WithTransfer(p, Gamma22));
+//! let blurhash = compute_dct_iter(pixels, 16, 16, 4, 3).into_blurhash();
+//!
+//! let placeholder: Vec<[u8; 3]> = decode(&blurhash, 1.).unwrap().to_rgb8_with(32, 32, &Gamma22);
+//! ```
+//!
+//! [`srgb_to_linear`]: ../convert/fn.srgb_to_linear.html
+//! [`linear_to_srgb`]: ../convert/fn.linear_to_srgb.html
+//! [`EncodeOptions`]: ../options/struct.EncodeOptions.html
+//! [`DCTResult::to_rgb8_with`]: ../struct.DCTResult.html#method.to_rgb8_with
+
+use crate::DCTResult;
+use crate::convert::{AsLinear, Linear, sign_pow};
+
+/// A transfer function (or its inverse) between the encoded values of a
+/// channel, between 0 and 1, and their linear values where 1 is the reference
+/// white.
+///
+/// Values outside of this range are handled by each transfer function:
+/// [`Srgb`] and [`Bt2020`] extend their linear segment to negative values and
+/// their curve above 1, [`Gamma22`] and [`AdobeRgb`] mirror their curve for
+/// negative values, [`Identity`] keeps every value, while [`Pq`] and [`Hlg`]
+/// clamp their input to the range they define.
+pub trait TransferFunction {
+    /// Decode a value between 0 and 1 to the linear space.
+    fn decode(&self, encoded: f32) -> f32;
+
+    /// Encode a positive linear value to a value between 0 and 1.
+    fn encode(&self, linear: f32) -> f32;
+}
+
+/// The sRGB transfer function, same as [`srgb_to_linear`] and
+/// [`linear_to_srgb`]
+///
+/// [`srgb_to_linear`]: ../convert/fn.srgb_to_linear.html
+/// [`linear_to_srgb`]: ../convert/fn.linear_to_srgb.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Srgb;
+
+impl TransferFunction for Srgb {
+    fn decode(&self, encoded: f32) -> f32 {
+        if encoded <= 0.04045 {
+            encoded / 12.92
+        } else {
+            ((encoded + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        if linear <= 0.0031308 {
+            linear * 12.92
+        } else {
+            1.055 * linear.powf(1. / 2.4) - 0.055
+        }
+    }
+}
+
+/// A pure gamma of 2.2
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gamma22;
+
+impl TransferFunction for Gamma22 {
+    fn decode(&self, encoded: f32) -> f32 {
+        sign_pow(encoded, 2.2)
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        sign_pow(linear, 1. / 2.2)
+    }
+}
+
+/// The Adobe RGB (1998) transfer function, a pure gamma of 563/256
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct AdobeRgb;
+
+impl AdobeRgb {
+    const GAMMA: f32 = 563. / 256.;
+}
+
+impl TransferFunction for AdobeRgb {
+    fn decode(&self, encoded: f32) -> f32 {
+        sign_pow(encoded, AdobeRgb::GAMMA)
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        sign_pow(linear, 1. / AdobeRgb::GAMMA)
+    }
+}
+
+/// The BT.2020 (and BT.709) transfer function
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Bt2020;
+
+impl Bt2020 {
+    const ALPHA: f32 = 1.0992968;
+    const BETA: f32 = 0.01805397;
+}
+
+impl TransferFunction for Bt2020 {
+    fn decode(&self, encoded: f32) -> f32 {
+        if encoded < 4.5 * Bt2020::BETA {
+            encoded / 4.5
+        } else {
+            ((encoded + Bt2020::ALPHA - 1.) / Bt2020::ALPHA).powf(1. / 0.45)
+        }
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        if linear < Bt2020::BETA {
+            linear * 4.5
+        } else {
+            Bt2020::ALPHA * linear.powf(0.45) - (Bt2020::ALPHA - 1.)
+        }
+    }
+}
+
+/// Linear passthrough, for values which are already in the linear space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Identity;
+
+impl TransferFunction for Identity {
+    fn decode(&self, encoded: f32) -> f32 {
+        encoded
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        linear
+    }
+}
+
+/// The Perceptual Quantizer (SMPTE ST 2084) used by HDR10. The linear values
+/// are scaled so that 1 is the HDR reference white of 203 cd/m²
+/// (ITU-R BT.2408), highlights go up to about 49 and should be tone-mapped
+/// (see [`ToneMap`]).
+///
+/// [`ToneMap`]: ../options/enum.ToneMap.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Pq;
+
+impl Pq {
+    const M1: f32 = 2610. / 16384.;
+    const M2: f32 = 2523. / 4096. * 128.;
+    const C1: f32 = 3424. / 4096.;
+    const C2: f32 = 2413. / 4096. * 32.;
+    const C3: f32 = 2392. / 4096. * 32.;
+    /// Luminance of the reference white relative to the PQ peak of 10000 cd/m²
+    const WHITE: f32 = 203. / 10000.;
+}
+
+impl TransferFunction for Pq {
+    fn decode(&self, encoded: f32) -> f32 {
+        let e = encoded.clamp(0., 1.).powf(1. / Pq::M2);
+        let y = ((e - Pq::C1).max(0.) / (Pq::C2 - Pq::C3 * e)).powf(1. / Pq::M1);
+        y / Pq::WHITE
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        let y = (linear * Pq::WHITE).clamp(0., 1.).powf(Pq::M1);
+        ((Pq::C1 + Pq::C2 * y) / (1. + Pq::C3 * y)).powf(Pq::M2)
+    }
+}
+
+/// The Hybrid Log-Gamma transfer function (ITU-R BT.2100) converting to scene
+/// light. The linear values are scaled so that 1 is the reference white
+/// (an encoded value of 0.75, ITU-R BT.2408), highlights go up to about 3.8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Hlg;
+
+impl Hlg {
+    const A: f32 = 0.17883277;
+    const B: f32 = 0.28466892;
+    const C: f32 = 0.5599107;
+    /// Scene light of the reference white
+    const WHITE: f32 = 0.26496256;
+}
+
+impl TransferFunction for Hlg {
+    fn decode(&self, encoded: f32) -> f32 {
+        let e = encoded.clamp(0., 1.);
+        let scene = if e <= 0.5 {
+            e * e / 3.
+        } else {
+            (((e - Hlg::C) / Hlg::A).exp() + Hlg::B) / 12.
+        };
+        scene / Hlg::WHITE
+    }
+
+    fn encode(&self, linear: f32) -> f32 {
+        let scene = (linear * Hlg::WHITE).clamp(0., 1.);
+        if scene <= 1. / 12. {
+            (3. * scene).sqrt()
+        } else {
+            Hlg::A * (12. * scene - Hlg::B).ln() + Hlg::C
+        }
+    }
+}
+
+/// Channel values of a pixel normalized between 0 and 1, used by
+/// [`WithTransfer`]
+pub trait Normalized {
+    /// Returns the color channels normalized between 0 and 1.
+    fn normalized(&self) -> [f32; 3];
+
+    /// Returns the opacity of the pixel between 0 (transparent) and 1 (opaque).
+    fn alpha(&self) -> f32 {
+        1.
+    }
+}
+
+impl Normalized for [u8; 3] {
+    fn normalized(&self) -> [f32; 3] {
+        self.map(|c| c as f32 / 255.)
+    }
+}
+
+impl Normalized for [u8; 4] {
+    fn normalized(&self) -> [f32; 3] {
+        [self[0], self[1], self[2]].map(|c| c as f32 / 255.)
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3] as f32 / 255.
+    }
+}
+
+impl Normalized for [u16; 3] {
+    fn normalized(&self) -> [f32; 3] {
+        self.map(|c| c as f32 / 65535.)
+    }
+}
+
+impl Normalized for [u16; 4] {
+    fn normalized(&self) -> [f32; 3] {
+        [self[0], self[1], self[2]].map(|c| c as f32 / 65535.)
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3] as f32 / 65535.
+    }
+}
+
+impl Normalized for [f32; 3] {
+    fn normalized(&self) -> [f32; 3] {
+        *self
+    }
+}
+
+impl Normalized for [f32; 4] {
+    fn normalized(&self) -> [f32; 3] {
+        [self[0], self[1], self[2]]
+    }
+
+    fn alpha(&self) -> f32 {
+        self[3].clamp(0., 1.)
+    }
+}
+
+/// A pixel whose channels are encoded with the given transfer function
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WithTransfer<P, F>(pub P, pub F);
+
+impl<P: Normalized, F: TransferFunction> AsLinear for WithTransfer<P, F> {
+    fn as_linear(&self) -> Linear {
+        self.0.normalized().map(|c| self.1.decode(c))
+    }
+
+    fn alpha(&self) -> f32 {
+        self.0.alpha()
+    }
+}
+
+impl<P: Normalized, F: TransferFunction> AsLinear for &WithTransfer<P, F> {
+    fn as_linear(&self) -> Linear {
+        self.0.normalized().map(|c| self.1.decode(c))
+    }
+
+    fn alpha(&self) -> f32 {
+        self.0.alpha()
+    }
+}
+
+/// Encode a linear value to 8 bits with a transfer function
+#[inline]
+fn quantize(transfer: &impl TransferFunction, linear: f32) -> u8 {
+    (transfer.encode(linear) * 255. + 0.5).floor().clamp(0., 255.) as u8
+}
+
+impl DCTResult {
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image. This function allocates a vector of (width * height) pixels
+    /// encoded with the given transfer function as in [RR, GG, BB].
+    ///
+    /// Note: the linear values are clamped between 0 and 1 (the reference
+    /// white) before being encoded, so the high dynamic range transfer
+    /// functions never go above the code value of the reference white (about
+    /// 0.58 for [`Pq`] and 0.75 for [`Hlg`]).
+    pub fn to_rgb8_with(&self, width: usize, height: usize, transfer: &impl TransferFunction) -> Vec<[u8; 3]> {
+        self.map_pixels(width, height, |col| col.map(|c| quantize(transfer, c)))
+    }
+
+    /// Generate an image from this DCT Result to recreate (sort of) the original
+    /// image. This function allocates a vector of (width * height) pixels
+    /// encoded with the given transfer function as in [RR, GG, BB, AA] (alpha
+    /// will always be 255). The linear values are clamped as in
+    /// [`to_rgb8_with`](Self::to_rgb8_with).
+    pub fn to_rgba8_with(&self, width: usize, height: usize, transfer: &impl TransferFunction) -> Vec<[u8; 4]> {
+        self.map_pixels(width, height, |col| {
+            let [r, g, b] = col.map(|c| quantize(transfer, c));
+            [r, g, b, 255]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_dct, decode};
+    use crate::convert::{linear_to_srgb, srgb_to_linear};
+    use crate::tests::test_pixels;
+
+    fn assert_roundtrip(transfer: &impl TransferFunction) {
+        for i in 0..=20 {
+            let encoded = i as f32 / 20.;
+            let roundtrip = transfer.encode(transfer.decode(encoded));
+            assert!((roundtrip - encoded).abs() < 1e-4, "{encoded} != {roundtrip}");
+        }
+    }
+
+    #[test]
+    fn test_transfer_functions() {
+        assert_roundtrip(&Srgb);
+        assert_roundtrip(&Gamma22);
+        assert_roundtrip(&AdobeRgb);
+        assert_roundtrip(&Bt2020);
+        assert_roundtrip(&Identity);
+        assert_roundtrip(&Pq);
+        assert_roundtrip(&Hlg);
+
+        for i in 0..=255u8 {
+            assert_eq!(Srgb.decode(i as f32 / 255.), srgb_to_linear(i));
+            assert_eq!(quantize(&Srgb, srgb_to_linear(i)), i);
+            assert_eq!(linear_to_srgb(srgb_to_linear(i)), i);
+        }
+
+        assert!((Pq.decode(0.58) - 1.).abs() < 0.01); // 203 cd/m²
+        assert!((Pq.decode(1.) - 10000. / 203.).abs() < 0.01);
+        assert!((Hlg.decode(0.75) - 1.).abs() < 1e-4);
+        assert_eq!(Gamma22.decode(0.5), 0.5f32.powf(2.2));
+        assert_eq!(AdobeRgb.decode(-0.5), -AdobeRgb.decode(0.5));
+        assert!((Bt2020.decode(0.5) - 0.2597).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_encode_with_transfer() {
+        let image = test_pixels();
+        let expected = compute_dct(&image, 8, 8, 4, 3).into_blurhash();
+
+        let srgb: Vec<_> = image.iter().map(|&p| WithTransfer(p, Srgb)).collect();
+        assert_eq!(compute_dct(&srgb, 8, 8, 4, 3).into_blurhash(), expected);
+
+        let linear: Vec<_> = image.iter().map(|p| WithTransfer(p.as_linear(), Identity)).collect();
+        assert_eq!(compute_dct(&linear, 8, 8, 4, 3).into_blurhash(), expected);
+
+        let gamma: Vec<_> = image.iter().map(|&p| WithTransfer(p, Gamma22)).collect();
+        assert_ne!(compute_dct(&gamma, 8, 8, 4, 3).into_blurhash(), expected);
+    }
+
+    #[test]
+    fn test_render_with_transfer() {
+        let dct = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap();
+        assert_eq!(dct.to_rgb8_with(8, 6, &Srgb), dct.to_rgb8(8, 6));
+        assert_eq!(dct.to_rgba8_with(8, 6, &Srgb), dct.to_rgba8(8, 6));
+        assert_ne!(dct.to_rgb8_with(8, 6, &Gamma22), dct.to_rgb8(8, 6));
+
+        // the reference white is the brightest value
+        let white = decode("00TSUA", 1.).unwrap();
+        assert_eq!(white.to_rgb8_with(1, 1, &Pq), [[148; 3]]);
+        assert_eq!(white.to_rgb8_with(1, 1, &Hlg), [[191; 3]]);
+    }
+}
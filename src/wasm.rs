@@ -0,0 +1,57 @@
+//! WebAssembly bindings using `wasm-bindgen` (requires the `wasm` feature)
+//!
+//! The pixels are exchanged as `Uint8ClampedArray` in the RGBA format used by
+//! the `ImageData` of the canvas API. The module is built with
+//! `cargo rustc --release --lib --crate-type cdylib --target wasm32-unknown-unknown --features wasm`
+//! followed by `wasm-bindgen`.
+//!
+//! #### Example
+//! ```js
+//! import { encode, decode } from "fast-blurhash";
+//!
+//! const data = ctx.getImageData(0, 0, width, height);
+//! const blurhash = encode(data.data, width, height, 4, 3);
+//! const pixels = decode(blurhash, 32, 32, 1.0);
+//! ctx.putImageData(new ImageData(pixels, 32, 32), 0, 0);
+//! ```
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::Clamped;
+use crate::compute_dct;
+
+/// Compute the blurhash of an image of (width * height) RGBA pixels, alpha is
+/// ignored. The number of X and Y components must be between 1 and 9.
+#[wasm_bindgen]
+pub fn encode(pixels: Clamped<Vec<u8>>, width: u32, height: u32, x_components: u32, y_components: u32) -> Result<String, JsError> {
+    let (width, height) = (width as usize, height as usize);
+    if pixels.len() < image_len(width, height)? {
+        return Err(JsError::new("The pixels array must contain at least width * height * 4 values"))
+    }
+
+    if !(1..=9).contains(&x_components) || !(1..=9).contains(&y_components) {
+        return Err(JsError::new("The number of X and Y components must be between 1 and 9"))
+    }
+
+    let pixels: Vec<[u8; 4]> = pixels.chunks_exact(4).map(|p| [p[0], p[1], p[2], p[3]]).collect();
+    Ok(compute_dct(&pixels, width, height, x_components as usize, y_components as usize).into_blurhash())
+}
+
+/// Number of values of an image of (width * height) RGBA pixels, which can
+/// overflow on wasm32 with untrusted dimensions.
+fn image_len(width: usize, height: usize) -> Result<usize, JsError> {
+    width.checked_mul(height)
+        .and_then(|len| len.checked_mul(4))
+        .ok_or_else(|| JsError::new("The image dimensions are too large"))
+}
+
+/// Decode a blurhash into an image of (width * height) RGBA pixels (alpha will
+/// always be 255) which can be used to create an `ImageData`.
+#[wasm_bindgen]
+pub fn decode(blurhash: &str, width: u32, height: u32, punch: f32) -> Result<Clamped<Vec<u8>>, JsError> {
+    let (width, height) = (width as usize, height as usize);
+    image_len(width, height)?;
+
+    let dct = crate::decode(blurhash, punch).map_err(|err| JsError::new(&err.to_string()))?;
+    let pixels = dct.to_rgba8(width, height);
+    Ok(Clamped(pixels.into_iter().flatten().collect()))
+}
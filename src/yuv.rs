@@ -0,0 +1,312 @@
+//! Compute blurhashes directly from 8-bit YUV (Y'CbCr) video frames
+//!
+//! The frames are converted pixel by pixel to R'G'B' with the BT.601 or BT.709
+//! matrix, decoded to linear RGB with the sRGB transfer function and fed to the
+//! DCT without allocating (nor quantizing to) an intermediate RGB image.
+//! Chroma planes are sampled with the nearest neighbour.
+//!
+//! #### Example
+//! ```
+//! use fast_blurhash::yuv::{compute_dct_yuv, I420Frame, Matrix, Range};
+//!
+//! let (width, height) = (8, 8);
+//! let (y, u, v) = (vec![128u8; 64], vec![128u8; 16], vec![128u8; 16]);
+//! let frame = I420Frame::new(&y, &u, &v, width, height);
+//! let blurhash = compute_dct_yuv(&frame, Matrix::Bt709, Range::Limited, 4, 3).into_blurhash();
+//! ```
+
+use crate::{DCTResult, multiply_basis, normalize_and_max};
+use crate::convert::*;
+use crate::options::EncodeOptions;
+use crate::transfer::{Srgb, TransferFunction};
+
+/// Matrix used to convert Y'CbCr to R'G'B'
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Matrix {
+    /// ITU-R BT.601, used by standard definition video and JPEG
+    Bt601,
+    /// ITU-R BT.709, used by high definition video
+    Bt709,
+}
+
+/// Range of the Y'CbCr values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Range {
+    /// Y' between 0 and 255, Cb and Cr between 0 and 255 (JPEG)
+    Full,
+    /// Y' between 16 and 235, Cb and Cr between 16 and 240 (most video)
+    Limited,
+}
+
+/// A frame of 8-bit Y'CbCr pixels
+pub trait YuvFrame {
+    /// Retrieve the dimensions (width, height) of the frame
+    fn dimensions(&self) -> (usize, usize);
+
+    /// Retrieve the [Y', Cb, Cr] values of the pixel (x, y)
+    fn sample(&self, x: usize, y: usize) -> [u8; 3];
+}
+
+/// Planar 4:2:0 frame: a Y' plane followed by quarter-size U (Cb) and V (Cr)
+/// planes, such as I420 or YV12 (with the U and V planes swapped).
+#[derive(Debug, Clone, Copy)]
+pub struct I420Frame<'a> {
+    y: &'a [u8],
+    u: &'a [u8],
+    v: &'a [u8],
+    y_stride: usize,
+    uv_stride: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> I420Frame<'a> {
+    /// Create a frame of tightly packed planes. The Y' plane must have at least
+    /// width * height bytes and each chroma plane at least
+    /// ceil(width / 2) * ceil(height / 2) bytes.
+    pub fn new(y: &'a [u8], u: &'a [u8], v: &'a [u8], width: usize, height: usize) -> Self {
+        Self::with_strides(y, u, v, width, height, width, width.div_ceil(2))
+    }
+
+    /// Create a frame with the given number of bytes between the start of two
+    /// rows of the Y' plane (`y_stride`) and of the chroma planes (`uv_stride`).
+    pub fn with_strides(y: &'a [u8], u: &'a [u8], v: &'a [u8], width: usize, height: usize, y_stride: usize, uv_stride: usize) -> Self {
+        let (chroma_width, chroma_height) = (width.div_ceil(2), height.div_ceil(2));
+        assert!(y_stride >= width && uv_stride >= chroma_width);
+        assert!(height == 0 || y.len() >= y_stride * (height - 1) + width);
+        assert!(height == 0 || u.len() >= uv_stride * (chroma_height - 1) + chroma_width);
+        assert!(height == 0 || v.len() >= uv_stride * (chroma_height - 1) + chroma_width);
+        I420Frame { y, u, v, y_stride, uv_stride, width, height }
+    }
+}
+
+impl YuvFrame for I420Frame<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn sample(&self, x: usize, y: usize) -> [u8; 3] {
+        let chroma = (y / 2) * self.uv_stride + x / 2;
+        [self.y[y * self.y_stride + x], self.u[chroma], self.v[chroma]]
+    }
+}
+
+/// Semi-planar 4:2:0 frame: a Y' plane followed by a quarter-size plane of
+/// interleaved U (Cb) and V (Cr) values.
+#[derive(Debug, Clone, Copy)]
+pub struct Nv12Frame<'a> {
+    y: &'a [u8],
+    uv: &'a [u8],
+    y_stride: usize,
+    uv_stride: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> Nv12Frame<'a> {
+    /// Create a frame of tightly packed planes. The Y' plane must have at least
+    /// width * height bytes and the UV plane at least
+    /// 2 * ceil(width / 2) * ceil(height / 2) bytes.
+    pub fn new(y: &'a [u8], uv: &'a [u8], width: usize, height: usize) -> Self {
+        Self::with_strides(y, uv, width, height, width, 2 * width.div_ceil(2))
+    }
+
+    /// Create a frame with the given number of bytes between the start of two
+    /// rows of the Y' plane (`y_stride`) and of the UV plane (`uv_stride`).
+    pub fn with_strides(y: &'a [u8], uv: &'a [u8], width: usize, height: usize, y_stride: usize, uv_stride: usize) -> Self {
+        let (chroma_width, chroma_height) = (2 * width.div_ceil(2), height.div_ceil(2));
+        assert!(y_stride >= width && uv_stride >= chroma_width);
+        assert!(height == 0 || y.len() >= y_stride * (height - 1) + width);
+        assert!(height == 0 || uv.len() >= uv_stride * (chroma_height - 1) + chroma_width);
+        Nv12Frame { y, uv, y_stride, uv_stride, width, height }
+    }
+}
+
+impl YuvFrame for Nv12Frame<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn sample(&self, x: usize, y: usize) -> [u8; 3] {
+        let chroma = (y / 2) * self.uv_stride + (x / 2) * 2;
+        [self.y[y * self.y_stride + x], self.uv[chroma], self.uv[chroma + 1]]
+    }
+}
+
+/// Packed 4:2:2 frame where every pair of pixels is stored as Y'0, U, Y'1, V
+/// (also known as YUY2).
+#[derive(Debug, Clone, Copy)]
+pub struct YuyvFrame<'a> {
+    data: &'a [u8],
+    stride: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<'a> YuyvFrame<'a> {
+    /// Create a frame of tightly packed rows, the data must have at least
+    /// 4 * ceil(width / 2) * height bytes.
+    pub fn new(data: &'a [u8], width: usize, height: usize) -> Self {
+        Self::with_stride(data, width, height, 4 * width.div_ceil(2))
+    }
+
+    /// Create a frame with the given number of bytes between the start of two
+    /// rows.
+    pub fn with_stride(data: &'a [u8], width: usize, height: usize, stride: usize) -> Self {
+        let row_len = 4 * width.div_ceil(2);
+        assert!(stride >= row_len);
+        assert!(height == 0 || data.len() >= stride * (height - 1) + row_len);
+        YuyvFrame { data, stride, width, height }
+    }
+}
+
+impl YuvFrame for YuyvFrame<'_> {
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn sample(&self, x: usize, y: usize) -> [u8; 3] {
+        let pair = y * self.stride + (x / 2) * 4;
+        [self.data[pair + (x % 2) * 2], self.data[pair + 1], self.data[pair + 3]]
+    }
+}
+
+/// Convert 8-bit [Y', Cb, Cr] values to [R', G', B'] values between 0 and 1
+pub fn yuv_to_rgb([y, cb, cr]: [u8; 3], matrix: Matrix, range: Range) -> [f32; 3] {
+    let (kr, kb) = match matrix {
+        Matrix::Bt601 => (0.299, 0.114),
+        Matrix::Bt709 => (0.2126, 0.0722),
+    };
+
+    let (y, cb, cr) = match range {
+        Range::Full => (y as f32 / 255., (cb as f32 - 128.) / 255., (cr as f32 - 128.) / 255.),
+        Range::Limited => ((y as f32 - 16.) / 219., (cb as f32 - 128.) / 224., (cr as f32 - 128.) / 224.),
+    };
+
+    let r = y + 2. * (1. - kr) * cr;
+    let b = y + 2. * (1. - kb) * cb;
+    let g = (y - kr * r - kb * b) / (1. - kr - kb);
+
+    [r, g, b].map(|c| c.clamp(0., 1.))
+}
+
+/// Compute the Discrete Cosine Transform on a Y'CbCr frame converted to RGB
+/// with the given matrix and range.
+///
+/// Note: To generate a valid blurhash, the number of X or/and Y components
+/// must be between 1 and 9. This is a limitation of the encoding scheme.
+pub fn compute_dct_yuv(frame: &impl YuvFrame, matrix: Matrix, range: Range, x_components: usize, y_components: usize) -> DCTResult {
+    compute_dct_yuv_with(frame, matrix, range, x_components, y_components, &EncodeOptions::default())
+}
+
+/// Same as [`compute_dct_yuv`] but with custom [`EncodeOptions`], for example
+/// to encode a BT.2020 frame. Y'CbCr frames are always opaque so the alpha mode
+/// has no effect.
+///
+/// [`EncodeOptions`]: ../options/struct.EncodeOptions.html
+pub fn compute_dct_yuv_with(frame: &impl YuvFrame, matrix: Matrix, range: Range, x_components: usize, y_components: usize, options: &EncodeOptions) -> DCTResult {
+    let (width, height) = frame.dimensions();
+    let mut currents: Vec<Factor> = vec![[0., 0., 0.]; x_components * y_components];
+
+    for y in 0..height {
+        let percent_y = y as f32 / height as f32;
+        for x in 0..width {
+            let percent_x = x as f32 / width as f32;
+
+            let linear = yuv_to_rgb(frame.sample(x, y), matrix, range).map(|c| Srgb.decode(c));
+            let col = options.linear_color(&linear);
+            multiply_basis(x_components, y_components, percent_x, percent_y, &col, &mut currents);
+        }
+    }
+
+    let ac_max = normalize_and_max(&mut currents, width * height);
+
+    DCTResult { ac_max, currents, x_components, y_components }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute_dct, compute_dct_with};
+    use crate::options::{ColorSpace, ToneMap};
+
+    /// Y'CbCr test frame of 6 * 4 pixels with constant chroma in each 2 * 2 block
+    fn test_frame() -> Vec<[u8; 3]> {
+        (0..24).map(|i| {
+            let (x, y) = (i % 6, i / 6);
+            let block = (y / 2) * 3 + x / 2;
+            [(16 + i * 9) as u8, (60 + block * 25) as u8, (200 - block * 20) as u8]
+        }).collect()
+    }
+
+    /// Linear RGB image of a Y'CbCr frame
+    fn to_linear(yuv: &[[u8; 3]], matrix: Matrix, range: Range) -> Vec<[f32; 3]> {
+        yuv.iter().map(|&p| yuv_to_rgb(p, matrix, range).map(|c| Srgb.decode(c))).collect()
+    }
+
+    #[test]
+    fn test_yuv_to_rgb() {
+        let to_rgb8 = |p, matrix, range| yuv_to_rgb(p, matrix, range).map(|c| (c * 255. + 0.5) as u8);
+        assert_eq!(to_rgb8([16, 128, 128], Matrix::Bt709, Range::Limited), [0, 0, 0]);
+        assert_eq!(to_rgb8([235, 128, 128], Matrix::Bt709, Range::Limited), [255, 255, 255]);
+        assert_eq!(to_rgb8([255, 128, 128], Matrix::Bt601, Range::Full), [255, 255, 255]);
+        // pure red, the reference Y'CbCr values are rounded
+        assert_eq!(to_rgb8([76, 85, 255], Matrix::Bt601, Range::Full), [254, 0, 0]);
+        assert_eq!(to_rgb8([63, 102, 240], Matrix::Bt709, Range::Limited), [255, 1, 0]);
+    }
+
+    #[test]
+    fn test_frames() {
+        let yuv = test_frame();
+        for (matrix, range) in [(Matrix::Bt601, Range::Full), (Matrix::Bt709, Range::Limited)] {
+            let expected = compute_dct(&to_linear(&yuv, matrix, range), 6, 4, 4, 3).into_blurhash();
+
+            let y: Vec<u8> = yuv.iter().map(|p| p[0]).collect();
+            let u: Vec<u8> = yuv.iter().step_by(2).take(3).chain(yuv[12..].iter().step_by(2).take(3)).map(|p| p[1]).collect();
+            let v: Vec<u8> = yuv.iter().step_by(2).take(3).chain(yuv[12..].iter().step_by(2).take(3)).map(|p| p[2]).collect();
+            let i420 = I420Frame::new(&y, &u, &v, 6, 4);
+            assert_eq!(compute_dct_yuv(&i420, matrix, range, 4, 3).into_blurhash(), expected);
+
+            let uv: Vec<u8> = u.iter().zip(&v).flat_map(|(&u, &v)| [u, v]).collect();
+            let nv12 = Nv12Frame::new(&y, &uv, 6, 4);
+            assert_eq!(compute_dct_yuv(&nv12, matrix, range, 4, 3).into_blurhash(), expected);
+
+            // YUYV is 4:2:2, each row has its own chroma
+            let yuyv: Vec<u8> = yuv.chunks(2).flat_map(|p| [p[0][0], p[0][1], p[1][0], p[0][2]]).collect();
+            let yuyv = YuyvFrame::new(&yuyv, 6, 4);
+            assert_eq!(compute_dct_yuv(&yuyv, matrix, range, 4, 3).into_blurhash(), expected);
+        }
+    }
+
+    #[test]
+    fn test_frame_with_options() {
+        let yuv = test_frame();
+        let linear = to_linear(&yuv, Matrix::Bt709, Range::Full);
+        let y: Vec<u8> = yuv.iter().map(|p| p[0]).collect();
+        let u: Vec<u8> = yuv.iter().step_by(2).take(3).chain(yuv[12..].iter().step_by(2).take(3)).map(|p| p[1]).collect();
+        let v: Vec<u8> = yuv.iter().step_by(2).take(3).chain(yuv[12..].iter().step_by(2).take(3)).map(|p| p[2]).collect();
+        let frame = I420Frame::new(&y, &u, &v, 6, 4);
+
+        let options = EncodeOptions { color_space: ColorSpace::Bt2020, tone_map: ToneMap::Reinhard, ..Default::default() };
+        let expected = compute_dct_with(&linear, 6, 4, 4, 3, &options).into_blurhash();
+        assert_eq!(compute_dct_yuv_with(&frame, Matrix::Bt709, Range::Full, 4, 3, &options).into_blurhash(), expected);
+        assert_ne!(compute_dct_yuv(&frame, Matrix::Bt709, Range::Full, 4, 3).into_blurhash(), expected);
+    }
+
+    #[test]
+    fn test_strides() {
+        let y = [16u8, 235, 0, 0, 235, 16, 0, 0];
+        let uv = [128u8, 128, 7, 7];
+        let frame = Nv12Frame::with_strides(&y, &uv, 2, 2, 4, 4);
+        assert_eq!([frame.sample(0, 0), frame.sample(1, 0), frame.sample(0, 1)], [[16, 128, 128], [235, 128, 128], [235, 128, 128]]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_plane_too_small() {
+        I420Frame::new(&[0; 16], &[0; 4], &[0; 3], 4, 4);
+    }
+}
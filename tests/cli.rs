@@ -0,0 +1,120 @@
+//! Runs the `blurhash` command-line tool, in particular the batch mode which
+//! reads its inputs from stdin.
+
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output, Stdio};
+
+fn blurhash(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_blurhash"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn().expect("Unable to run blurhash");
+    child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+/// Write a placeholder decoded from a blurhash to a PNG file
+fn write_png(name: &str, blurhash: &str) -> PathBuf {
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let dct = fast_blurhash::decode(blurhash, 1.).unwrap();
+    std::fs::write(&path, dct.to_png(16, 12)).unwrap();
+    path
+}
+
+#[test]
+fn encode_files() {
+    let white = write_png("cli_white.png", "00TSUA");
+    let missing = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("cli_missing.png");
+    let (white, missing) = (white.to_str().unwrap(), missing.to_str().unwrap());
+
+    let output = blurhash(&["encode", "-x", "1", "-y", "1", white], "");
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "0~TSUA\n");
+
+    let output = blurhash(&["encode", "-x", "1", "-y", "1", white, missing], "");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), format!("{white}\t0~TSUA\n"));
+    assert!(String::from_utf8_lossy(&output.stderr).starts_with(&format!("blurhash: {missing}: ")));
+}
+
+#[test]
+fn encode_16bit() {
+    // dark shades which can't be told apart in 8 bits
+    let img = image::ImageBuffer::from_fn(16, 12, |x, y| image::Rgb([x as u16 * 16, y as u16 * 16, 200]));
+    let img = image::DynamicImage::ImageRgb16(img);
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("cli_16bit.png");
+    img.save(&path).unwrap();
+
+    let output = blurhash(&["encode", path.to_str().unwrap()], "");
+    assert!(output.status.success());
+    let expected = fast_blurhash::encode_image(&img, 4, 3);
+    assert_eq!(stdout(&output), format!("{expected}\n"));
+    assert_ne!(expected, fast_blurhash::encode_image(&image::DynamicImage::ImageRgb8(img.to_rgb8()), 4, 3));
+}
+
+#[test]
+fn encode_stdin() {
+    let white = write_png("cli_batch_white.png", "00TSUA");
+    let image = write_png("cli_batch_image.png", "LlMF%n00%#MwS|WCWEM{R*bbWBbH");
+    let (white, image) = (white.to_str().unwrap(), image.to_str().unwrap());
+
+    let output = blurhash(&["encode", "-x", "1", "-y", "1"], &format!("{white}\n\n  {image}  \n"));
+    assert!(output.status.success());
+    let lines: Vec<String> = stdout(&output).lines().map(String::from).collect();
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], format!("{white}\t0~TSUA"));
+    let (file, hash) = lines[1].split_once('\t').unwrap();
+    assert_eq!((file, hash.len()), (image, 6));
+
+    let output = blurhash(&["encode", "-x", "1", "-y", "1", "-"], &format!("{white}\n"));
+    assert_eq!(stdout(&output), format!("{white}\t0~TSUA\n"));
+}
+
+#[test]
+fn inspect_stdin() {
+    let output = blurhash(&["inspect"], "00TSUA\nLlMF%n00%#MwS|WCWEM{R*bbWBbH\n");
+    assert!(output.status.success());
+    let stdout = stdout(&output);
+    assert!(stdout.starts_with("blurhash: 00TSUA\ncomponents: 1x1\naverage color: #ffffff\n"), "{stdout}");
+    assert!(stdout.contains("\n\nblurhash: LlMF%n00%#MwS|WCWEM{R*bbWBbH\ncomponents: 4x3\n"), "{stdout}");
+    assert_eq!(stdout.matches("AC (").count(), 11);
+}
+
+#[test]
+fn bad_hashes() {
+    let output = blurhash(&["inspect", "-"], "°0TSUA\n00TSUA\nLlMF%n00\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).starts_with("blurhash: 00TSUA\n"));
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stderr.lines().count(), 2, "{stderr}");
+    assert!(stderr.starts_with("blurhash: °0TSUA: The blurhash contains invalid base83 codes"), "{stderr}");
+
+    let output = blurhash(&["decode", "°0TSUA", "-o", "unused.png"], "");
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn invalid_arguments() {
+    let invalid: [&[&str]; 7] = [&[], &["resize"], &["encode", "-x", "0"], &["inspect", "-x", "4"], &["decode", "00TSUA"],
+        &["decode", "00TSUA", "-w", "0", "-o", "unused.png"], &["decode", "00TSUA", "-h", "0", "-o", "unused.png"]];
+    for args in invalid {
+        let output = blurhash(args, "");
+        assert_eq!(output.status.code(), Some(2), "{args:?}");
+        assert!(output.stdout.is_empty());
+        assert!(String::from_utf8_lossy(&output.stderr).starts_with("blurhash: "));
+    }
+
+    for args in [&["--help"][..], &["encode", "--help"], &["decode", "00TSUA", "--help"], &["inspect", "--help"]] {
+        let output = blurhash(args, "");
+        assert!(output.status.success(), "{args:?}");
+        assert!(stdout(&output).starts_with("Usage: blurhash <command> [options]"));
+        assert!(output.stderr.is_empty());
+    }
+}
@@ -0,0 +1,59 @@
+//! Checks the C header and runs the C test harness against the `cdylib`.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+fn manifest_dir() -> &'static Path {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn header_is_up_to_date() {
+    // only parse the ffi module, the rest of the public API is not exported
+    let mut generated = Vec::new();
+    cbindgen::Builder::new()
+        .with_config(cbindgen::Config::from_root_or_default(manifest_dir()))
+        .with_src(manifest_dir().join("src/ffi.rs"))
+        .generate()
+        .expect("Unable to generate the C header")
+        .write(&mut generated);
+
+    let path = manifest_dir().join("include/fast_blurhash.h");
+    if std::env::var_os("FAST_BLURHASH_BLESS").is_some() {
+        std::fs::write(&path, &generated).unwrap();
+    }
+
+    let header = std::fs::read_to_string(&path).unwrap();
+    assert!(header == String::from_utf8(generated).unwrap(), "include/fast_blurhash.h is outdated, \
+        regenerate it with `FAST_BLURHASH_BLESS=1 cargo test --features ffi --test ffi`");
+}
+
+#[test]
+#[cfg(unix)]
+fn c_harness() {
+    // the crate is only built as a cdylib on demand, in a separate target
+    // directory as the one of the tests is locked while they run
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi");
+    let status = Command::new(env!("CARGO"))
+        .args(["rustc", "--lib", "--crate-type", "cdylib", "--features", "ffi", "--manifest-path"])
+        .arg(manifest_dir().join("Cargo.toml"))
+        .arg("--target-dir").arg(&target_dir)
+        .status().expect("Unable to run cargo");
+    assert!(status.success(), "Unable to build the cdylib");
+
+    let lib_dir: PathBuf = target_dir.join("debug");
+    let exe = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi_harness");
+
+    let status = Command::new(std::env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg(manifest_dir().join("tests/ffi/harness.c"))
+        .arg("-I").arg(manifest_dir().join("include"))
+        .arg("-L").arg(&lib_dir)
+        .arg(format!("-Wl,-rpath,{}", lib_dir.display()))
+        .args(["-lfast_blurhash", "-Wall", "-Werror", "-o"]).arg(&exe)
+        .status().expect("Unable to run the C compiler");
+    assert!(status.success(), "Unable to compile the C harness");
+
+    // cargo adds its own deps folder to LD_LIBRARY_PATH which takes precedence over the rpath
+    let output = Command::new(&exe).env("LD_LIBRARY_PATH", &lib_dir).output().unwrap();
+    assert!(output.status.success(), "C harness failed:\n{}", String::from_utf8_lossy(&output.stderr));
+}
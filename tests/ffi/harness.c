@@ -0,0 +1,74 @@
+/* C test harness for the C ABI, compiled and run by tests/ffi.rs */
+
+#include <stdio.h>
+#include <string.h>
+#include "fast_blurhash.h"
+
+#define CHECK(cond) do { \
+    if (!(cond)) { \
+        fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, #cond); \
+        return 1; \
+    } \
+} while (0)
+
+#define CHECK_STATUS(expr, expected) do { \
+    enum FastBlurhashStatus status = (expr); \
+    if (status != (expected)) { \
+        fprintf(stderr, "%s:%d: %s returned %d (%s)\n", __FILE__, __LINE__, #expr, \
+                (int) status, fast_blurhash_strerror(status)); \
+        return 1; \
+    } \
+} while (0)
+
+static int test_encode_white(void) {
+    /* 4x4 RGBA image with 4 bytes of padding at the end of each row */
+    uint8_t pixels[4 * 20];
+    memset(pixels, 255, sizeof(pixels));
+
+    char blurhash[FAST_BLURHASH_MAX_LEN];
+    CHECK_STATUS(fast_blurhash_encode(pixels, 4, 4, 20, 4, 4, 4, blurhash, sizeof(blurhash)),
+                 FAST_BLURHASH_STATUS_OK);
+    CHECK(strcmp(blurhash, "U~TSUA~qfQ~q~q%MfQ%MfQfQfQfQ~q%MfQ%M") == 0);
+
+    char small[8];
+    CHECK_STATUS(fast_blurhash_encode(pixels, 4, 4, 20, 4, 4, 4, small, sizeof(small)),
+                 FAST_BLURHASH_STATUS_BUFFER_TOO_SMALL);
+    CHECK_STATUS(fast_blurhash_encode(pixels, 4, 4, 20, 2, 4, 4, blurhash, sizeof(blurhash)),
+                 FAST_BLURHASH_STATUS_INVALID_ARGUMENT);
+    return 0;
+}
+
+static int test_decode(void) {
+    uint8_t pixels[32 * 32 * 3];
+    CHECK_STATUS(fast_blurhash_decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.f, pixels, 32, 32, 32 * 3, 3),
+                 FAST_BLURHASH_STATUS_OK);
+
+    uint8_t rgba[2 * 2 * 4];
+    CHECK_STATUS(fast_blurhash_decode("00TSUA", 1.f, rgba, 2, 2, 2 * 4, 4), FAST_BLURHASH_STATUS_OK);
+    for (size_t i = 0; i < sizeof(rgba); i++) {
+        CHECK(rgba[i] == 255);
+    }
+
+    CHECK_STATUS(fast_blurhash_decode("LlMF%n00", 1.f, pixels, 32, 32, 32 * 3, 3),
+                 FAST_BLURHASH_STATUS_INVALID_LENGTH);
+    CHECK_STATUS(fast_blurhash_decode("00TSUA", -1.f, pixels, 32, 32, 32 * 3, 3),
+                 FAST_BLURHASH_STATUS_INVALID_PUNCH);
+    CHECK_STATUS(fast_blurhash_decode("~0TSUA", 1.f, pixels, 32, 32, 32 * 3, 3),
+                 FAST_BLURHASH_STATUS_UNSUPPORTED_MODE);
+    CHECK_STATUS(fast_blurhash_decode(NULL, 1.f, pixels, 32, 32, 32 * 3, 3),
+                 FAST_BLURHASH_STATUS_INVALID_ARGUMENT);
+    return 0;
+}
+
+static int test_strerror(void) {
+    CHECK(strcmp(fast_blurhash_strerror(FAST_BLURHASH_STATUS_OK), "Success") == 0);
+    CHECK(strcmp(fast_blurhash_strerror(1000), "Unknown status") == 0);
+    return 0;
+}
+
+int main(void) {
+    if (test_encode_white() != 0 || test_decode() != 0 || test_strerror() != 0) {
+        return 1;
+    }
+    return 0;
+}
@@ -0,0 +1,36 @@
+//! Tests of the WebAssembly bindings, run them under Node with
+//! `cargo test --target wasm32-unknown-unknown --features wasm --test wasm`
+//! (requires `wasm-bindgen-cli` which provides the test runner).
+#![cfg(target_arch = "wasm32")]
+
+use wasm_bindgen::Clamped;
+use wasm_bindgen_test::*;
+use fast_blurhash::wasm::{decode, encode};
+
+#[wasm_bindgen_test]
+fn encode_white() {
+    let pixels = Clamped(vec![255; 4 * 4 * 4]);
+    assert_eq!(encode(pixels, 4, 4, 4, 4).unwrap(), "U~TSUA~qfQ~q~q%MfQ%MfQfQfQfQ~q%MfQ%M");
+}
+
+#[wasm_bindgen_test]
+fn encode_invalid() {
+    assert!(encode(Clamped(vec![255; 4 * 4 * 3]), 4, 4, 4, 4).is_err());
+    assert!(encode(Clamped(vec![255; 4 * 4 * 4]), 4, 4, 10, 4).is_err());
+    // width * height * 4 overflows on wasm32
+    assert!(encode(Clamped(vec![255; 4 * 4 * 4]), 1 << 16, 1 << 14, 4, 4).is_err());
+}
+
+#[wasm_bindgen_test]
+fn decode_rgba() {
+    let pixels = decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 32, 24, 1.).unwrap();
+    let expected = fast_blurhash::decode("LlMF%n00%#MwS|WCWEM{R*bbWBbH", 1.).unwrap().to_rgba8(32, 24);
+    assert_eq!(pixels.0, expected.concat());
+}
+
+#[wasm_bindgen_test]
+fn decode_invalid() {
+    assert!(decode("LlMF%n00", 32, 32, 1.).is_err());
+    assert!(decode("°0TSUA", 32, 32, 1.).is_err());
+    assert!(decode("00TSUA", u32::MAX, u32::MAX, 1.).is_err());
+}